@@ -4,6 +4,7 @@ use quote::quote;
 use syn::{punctuated::Punctuated, token::Comma, Attribute, ExprPath, Ident, Lit, Meta};
 
 use crate::parse::parse_module;
+use crate::parse::FieldsetExpr;
 use crate::parse::IdentOrLiteral;
 
 type Result = std::result::Result<TokenStream, syn::Error>;
@@ -13,12 +14,25 @@ pub struct ModelAdminExpander {
     ident: Ident,
     list_display: Option<Vec<IdentOrLiteral>>,
     editable_fields: Option<Vec<syn::Expr>>,
+    readonly_fields: Option<Vec<syn::Expr>>,
     auto_complete: Option<Vec<syn::Ident>>,
     ordering: Option<Vec<(syn::Expr, syn::Expr)>>,
     search_fields: Option<Vec<syn::Expr>>,
     format: Option<Ident>,
     initial_value: Option<Ident>,
     form_fields: Option<Vec<syn::Expr>>,
+    fieldsets: Option<Vec<FieldsetExpr>>,
+    permission: Option<Ident>,
+    actions: Option<Vec<Ident>>,
+    list_filter: Option<Vec<syn::Expr>>,
+    validate: Option<Ident>,
+    pagination: Option<Ident>,
+    search_mode: Option<Ident>,
+    count_strategy: Option<Ident>,
+    before_save: Option<Ident>,
+    after_save: Option<Ident>,
+    before_delete: Option<Ident>,
+    after_delete: Option<Ident>,
 }
 
 impl ModelAdminExpander {
@@ -26,12 +40,25 @@ impl ModelAdminExpander {
         let mut module = None;
         let mut list_display = None;
         let mut editable_fields = None;
+        let mut readonly_fields = None;
         let mut auto_complete = None;
         let mut ordering = None;
         let mut search_fields = None;
         let mut format = None;
         let mut initial_value = None;
         let mut form_fields = None;
+        let mut fieldsets = None;
+        let mut permission = None;
+        let mut actions = None;
+        let mut list_filter = None;
+        let mut validate = None;
+        let mut pagination = None;
+        let mut search_mode = None;
+        let mut count_strategy = None;
+        let mut before_save = None;
+        let mut after_save = None;
+        let mut before_delete = None;
+        let mut after_delete = None;
 
         attrs.iter().try_for_each(|attr| {
             if let Ok(list) = attr.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated) {
@@ -45,6 +72,9 @@ impl ModelAdminExpander {
                             } else if ident == "fields" {
                                 editable_fields =
                                     Some(super::parse::parse_editable_fields(ident, &nv)?);
+                            } else if ident == "readonly_fields" {
+                                readonly_fields =
+                                    Some(super::parse::parse_readonly_fields(ident, nv)?);
                             } else if ident == "auto_complete" {
                                 auto_complete =
                                     Some(super::parse::parse_auto_complete(ident, &nv)?);
@@ -61,6 +91,34 @@ impl ModelAdminExpander {
                             } else if ident == "form_fields" {
                                 form_fields =
                                     Some(super::parse::parse_form_fields(ident, nv)?.clone());
+                            } else if ident == "fieldsets" {
+                                fieldsets = Some(super::parse::parse_fieldsets(ident, nv)?);
+                            } else if ident == "permission" {
+                                permission =
+                                    Some(super::parse::parse_permission(ident, nv)?.clone());
+                            } else if ident == "actions" {
+                                actions = Some(super::parse::parse_actions(ident, nv)?);
+                            } else if ident == "list_filter" {
+                                list_filter = Some(super::parse::parse_list_filter(ident, nv)?);
+                            } else if ident == "validate" {
+                                validate = Some(super::parse::parse_validate(ident, nv)?.clone());
+                            } else if ident == "pagination" {
+                                pagination =
+                                    Some(super::parse::parse_pagination(ident, nv)?.clone());
+                            } else if ident == "search_mode" {
+                                search_mode =
+                                    Some(super::parse::parse_search_mode(ident, nv)?.clone());
+                            } else if ident == "count_strategy" {
+                                count_strategy =
+                                    Some(super::parse::parse_count_strategy(ident, nv)?.clone());
+                            } else if ident == "before_save" {
+                                before_save = Some(super::parse::parse_hook(ident, nv)?.clone());
+                            } else if ident == "after_save" {
+                                after_save = Some(super::parse::parse_hook(ident, nv)?.clone());
+                            } else if ident == "before_delete" {
+                                before_delete = Some(super::parse::parse_hook(ident, nv)?.clone());
+                            } else if ident == "after_delete" {
+                                after_delete = Some(super::parse::parse_hook(ident, nv)?.clone());
                             }
                         }
                     }
@@ -75,12 +133,25 @@ impl ModelAdminExpander {
             ident,
             list_display,
             editable_fields,
+            readonly_fields,
             auto_complete,
             ordering,
             search_fields,
             format,
             initial_value,
             form_fields,
+            fieldsets,
+            permission,
+            actions,
+            list_filter,
+            validate,
+            pagination,
+            search_mode,
+            count_strategy,
+            before_save,
+            after_save,
+            before_delete,
+            after_delete,
         })
     }
 
@@ -204,6 +275,30 @@ impl ModelAdminExpander {
         }
     }
 
+    fn expand_get_readonly_fields(&self) -> Result {
+        let ident = &self.ident;
+        let module = &self.module;
+        let readonly_fields = self.readonly_fields.clone().unwrap_or(vec![]);
+        Ok(quote!(
+            impl #ident {
+                fn get_readonly_fields() -> Vec<#module :: Column> {
+                    vec![#(#module :: Column:: #readonly_fields),*]
+                }
+
+                /// Columns written by an update, read-only columns other than the primary keys are left out.
+                fn get_updatable_fields() -> Vec<#module :: Column> {
+                    use seaorm_admin::sea_orm::Iden;
+                    let keys: Vec<String> = #ident::get_keys().iter().map(|x| x.to_string()).collect();
+                    let readonly: Vec<String> = #ident::get_readonly_fields().iter().map(|x| x.to_string()).collect();
+                    #ident::get_fields()
+                        .into_iter()
+                        .filter(|x| keys.contains(&x.to_string()) || !readonly.contains(&x.to_string()))
+                        .collect()
+                }
+            }
+        ))
+    }
+
     fn expand_get_keys(&self) -> Result {
         let ident = &self.ident;
         let module = &self.module;
@@ -273,9 +368,188 @@ impl ModelAdminExpander {
         }
     }
 
+    fn expand_permissions(&self) -> TokenStream {
+        if let Some(permission) = &self.permission {
+            quote!(
+                fn has_view_permission(&self, user: &seaorm_admin::AdminUser) -> bool {
+                    #permission(user, seaorm_admin::AdminPermission::View)
+                }
+
+                fn has_add_permission(&self, user: &seaorm_admin::AdminUser) -> bool {
+                    #permission(user, seaorm_admin::AdminPermission::Add)
+                }
+
+                fn has_change_permission(&self, user: &seaorm_admin::AdminUser) -> bool {
+                    #permission(user, seaorm_admin::AdminPermission::Change)
+                }
+
+                fn has_delete_permission(&self, user: &seaorm_admin::AdminUser) -> bool {
+                    #permission(user, seaorm_admin::AdminPermission::Delete)
+                }
+            )
+        } else {
+            quote!()
+        }
+    }
+
+    fn expand_actions(&self) -> TokenStream {
+        if let Some(actions) = &self.actions {
+            quote!(
+                fn get_actions(&self) -> Vec<seaorm_admin::AdminAction> {
+                    vec![#(seaorm_admin::AdminAction::new(stringify!(#actions), #actions)),*]
+                }
+            )
+        } else {
+            quote!()
+        }
+    }
+
+    fn expand_list_filter(&self) -> TokenStream {
+        let module = &self.module;
+        if let Some(list_filter) = &self.list_filter {
+            quote!(
+                fn list_filter(&self) -> Vec<String> {
+                    use seaorm_admin::sea_orm::Iden;
+                    vec![#(#module::Column::#list_filter.to_string()),*]
+                }
+            )
+        } else {
+            quote!()
+        }
+    }
+
+    fn expand_pagination(&self) -> TokenStream {
+        if let Some(pagination) = &self.pagination {
+            quote!(
+                fn get_pagination(&self) -> seaorm_admin::Pagination {
+                    seaorm_admin::Pagination::#pagination
+                }
+            )
+        } else {
+            quote!()
+        }
+    }
+
+    fn expand_search_mode(&self) -> TokenStream {
+        if let Some(search_mode) = &self.search_mode {
+            quote!(
+                fn get_search_mode(&self) -> seaorm_admin::SearchMode {
+                    seaorm_admin::SearchMode::#search_mode
+                }
+            )
+        } else {
+            quote!()
+        }
+    }
+
+    fn expand_fieldsets(&self) -> TokenStream {
+        let module = &self.module;
+        if let Some(fieldsets) = &self.fieldsets {
+            let fieldsets: Vec<_> = fieldsets
+                .iter()
+                .map(|x| {
+                    let title = &x.title;
+                    let collapsed = x.collapsed;
+                    let fields: Vec<_> = x
+                        .fields
+                        .iter()
+                        .map(|f| match f {
+                            IdentOrLiteral::Path(p) => quote!(#module::Column::#p.to_string()),
+                            IdentOrLiteral::Literal(l) => quote!(#l.to_string()),
+                        })
+                        .collect();
+                    quote!(seaorm_admin::Fieldset::new(#title, vec![#(#fields),*], #collapsed))
+                })
+                .collect();
+            quote!(
+                fn get_fieldsets(&self) -> Vec<seaorm_admin::Fieldset> {
+                    use seaorm_admin::sea_orm::Iden;
+                    vec![#(#fieldsets),*]
+                }
+            )
+        } else {
+            quote!()
+        }
+    }
+
+    fn expand_count_strategy(&self) -> TokenStream {
+        if let Some(count_strategy) = &self.count_strategy {
+            quote!(
+                fn get_count_strategy(&self) -> seaorm_admin::CountStrategy {
+                    seaorm_admin::CountStrategy::#count_strategy
+                }
+            )
+        } else {
+            quote!()
+        }
+    }
+
+    fn expand_hooks(&self) -> TokenStream {
+        let mut hooks = Vec::new();
+        if let Some(before_save) = &self.before_save {
+            hooks.push(quote!(
+                async fn before_save(
+                    &self,
+                    conn: &seaorm_admin::sea_orm::DatabaseTransaction,
+                    value: seaorm_admin::Json,
+                    user: &seaorm_admin::AdminUser,
+                    operation: seaorm_admin::SaveOperation,
+                ) -> seaorm_admin::Result<seaorm_admin::Json> {
+                    #before_save(conn, value, user, operation).await
+                }
+            ));
+        }
+        if let Some(after_save) = &self.after_save {
+            hooks.push(quote!(
+                async fn after_save(
+                    &self,
+                    conn: &seaorm_admin::sea_orm::DatabaseTransaction,
+                    row: &seaorm_admin::Json,
+                    user: &seaorm_admin::AdminUser,
+                    operation: seaorm_admin::SaveOperation,
+                ) -> seaorm_admin::Result<()> {
+                    #after_save(conn, row, user, operation).await
+                }
+            ));
+        }
+        if let Some(before_delete) = &self.before_delete {
+            hooks.push(quote!(
+                async fn before_delete(
+                    &self,
+                    conn: &seaorm_admin::sea_orm::DatabaseTransaction,
+                    key: &seaorm_admin::Json,
+                    user: &seaorm_admin::AdminUser,
+                ) -> seaorm_admin::Result<()> {
+                    #before_delete(conn, key, user).await
+                }
+            ));
+        }
+        if let Some(after_delete) = &self.after_delete {
+            hooks.push(quote!(
+                async fn after_delete(
+                    &self,
+                    conn: &seaorm_admin::sea_orm::DatabaseTransaction,
+                    key: &seaorm_admin::Json,
+                    user: &seaorm_admin::AdminUser,
+                ) -> seaorm_admin::Result<()> {
+                    #after_delete(conn, key, user).await
+                }
+            ));
+        }
+        quote!(#(#hooks)*)
+    }
+
     fn expand_impl(&self) -> Result {
         let ident = &self.ident;
         let module = &self.module;
+        let permissions = self.expand_permissions();
+        let actions = self.expand_actions();
+        let list_filter = self.expand_list_filter();
+        let hooks = self.expand_hooks();
+        let pagination = self.expand_pagination();
+        let count_strategy = self.expand_count_strategy();
+        let fieldsets = self.expand_fieldsets();
+        let search_mode = self.expand_search_mode();
 
         Ok(quote!(
             #[seaorm_admin::async_trait]
@@ -319,6 +593,42 @@ impl ModelAdminExpander {
                     #ident::get_form_fields_impl()
                 }
 
+                fn get_readonly_fields(&self) -> Vec<String> {
+                    use seaorm_admin::sea_orm::Iden;
+                    #ident::get_readonly_fields().into_iter().map(|x| x.to_string()).collect()
+                }
+
+                #fieldsets
+
+                fn get_relations(&self) -> Vec<seaorm_admin::sea_orm::RelationDef> {
+                    use seaorm_admin::sea_orm::{Iterable, RelationTrait};
+                    #module::Relation::iter().map(|x| x.def()).collect()
+                }
+
+                #permissions
+
+                #actions
+
+                #list_filter
+
+                #hooks
+
+                fn default_ordering(&self) -> Vec<(String, seaorm_admin::sea_orm::Order)> {
+                    use seaorm_admin::sea_orm::Iden;
+                    #ident::get_ordering().into_iter().map(|(c, o)| (c.to_string(), o)).collect()
+                }
+
+                #pagination
+
+                #count_strategy
+
+                fn get_search_fields(&self) -> Vec<String> {
+                    use seaorm_admin::sea_orm::Iden;
+                    #ident::get_search_fields().into_iter().map(|x| x.to_string()).collect()
+                }
+
+                #search_mode
+
                 async fn list(
                     &self,
                     conn: &seaorm_admin::sea_orm::DatabaseConnection,
@@ -327,10 +637,31 @@ impl ModelAdminExpander {
                     #ident::list_impl(conn, param).await
                 }
 
+                async fn count(&self, conn: &seaorm_admin::sea_orm::DatabaseConnection, cond: &seaorm_admin::sea_orm::Condition) -> seaorm_admin::Result<u64> {
+                    seaorm_admin::count_rows::<#module::Entity>(conn, cond).await
+                }
+
+                async fn estimate_count(&self, conn: &seaorm_admin::sea_orm::DatabaseConnection, cond: &seaorm_admin::sea_orm::Condition) -> seaorm_admin::Result<Option<u64>> {
+                    seaorm_admin::estimate_rows::<#module::Entity>(conn, cond).await
+                }
+
+                async fn count_by(&self, conn: &seaorm_admin::sea_orm::DatabaseConnection, cond: &seaorm_admin::sea_orm::Condition, column: &str, limit: u64) -> seaorm_admin::Result<Vec<(seaorm_admin::Json, u64)>> {
+                    seaorm_admin::count_rows_by::<#module::Entity>(conn, cond, column, limit).await
+                }
+
                 async fn get(&self, conn: &seaorm_admin::sea_orm::DatabaseConnection, cond: &seaorm_admin::sea_orm::Condition) -> seaorm_admin::Result<Option<seaorm_admin::Json>> {
                     #ident::get_impl(conn, cond).await
                 }
 
+                fn sanitize_json(&self, value: &seaorm_admin::Json) -> seaorm_admin::Result<()> {
+                    let mut model = #module::ActiveModel { ..Default::default() };
+                    seaorm_admin::set_from_json(&mut model, &#ident::get_fields(), value)
+                }
+
+                async fn get_with_txn(&self, conn: &seaorm_admin::sea_orm::DatabaseTransaction, cond: &seaorm_admin::sea_orm::Condition) -> seaorm_admin::Result<Option<seaorm_admin::Json>> {
+                    #ident::get_impl(conn, cond).await
+                }
+
                 async fn insert(&self, conn: &seaorm_admin::sea_orm::DatabaseTransaction, value: &seaorm_admin::Json) -> seaorm_admin::Result<seaorm_admin::Json> {
                     #ident::insert_impl(conn, value).await
                 }
@@ -342,6 +673,11 @@ impl ModelAdminExpander {
                 async fn delete(&self, conn: &seaorm_admin::sea_orm::DatabaseTransaction, value: &seaorm_admin::sea_orm::Condition) -> seaorm_admin::Result<u64> {
                     #ident::delete_impl(conn, value).await
                 }
+
+                async fn delete_many(&self, conn: &seaorm_admin::sea_orm::DatabaseTransaction, cond: &seaorm_admin::sea_orm::Condition) -> seaorm_admin::Result<u64> {
+                    use seaorm_admin::sea_orm::{EntityTrait, QueryFilter};
+                    Ok(#module::Entity::delete_many().filter(cond.clone()).exec(conn).await?.rows_affected)
+                }
             }
         ))
     }
@@ -467,7 +803,13 @@ impl ModelAdminExpander {
                         }
                     }).flatten());
 
-                let mut fields: Vec<seaorm_admin::AdminField> = #ident::get_editable_fields()
+                let mut editable_fields = #ident::get_editable_fields();
+                for col in #ident::get_readonly_fields() {
+                    if !editable_fields.iter().any(|x| x.to_string() == col.to_string()) {
+                        editable_fields.push(col);
+                    }
+                }
+                let mut fields: Vec<seaorm_admin::AdminField> = editable_fields
                     .into_iter()
                     .filter(|x| !ac_col_set.contains(&x.to_string()))
                     .filter(|x| !ac_col_set.contains(&x.to_string()))
@@ -492,10 +834,15 @@ impl ModelAdminExpander {
                 conn: &seaorm_admin::sea_orm::DatabaseConnection,
                 param: &seaorm_admin::ListParam,
             ) -> seaorm_admin::Result<(u64, Vec<seaorm_admin::Json>)> {
-                use seaorm_admin::sea_orm::{EntityTrait, QuerySelect, PaginatorTrait, QueryFilter};
+                use seaorm_admin::sea_orm::{EntityTrait, QuerySelect, PaginatorTrait, QueryFilter, QueryOrder};
 
                 let fields = #ident::get_fields();
                 let qs = #module::Entity::find();
+                let qs = if let Some(rank) = &param.rank {
+                    qs.order_by(rank.clone(), seaorm_admin::sea_orm::Order::Desc)
+                } else {
+                    qs
+                };
                 let qs = if param.ordering.len() > 0 {
                     seaorm_admin::set_ordering_from_query(qs, &param.ordering, &#ident::get_fields())?
                 } else {
@@ -507,9 +854,9 @@ impl ModelAdminExpander {
                 } else {
                     qs.filter(param.cond.clone())
                 };
+                let count = if param.count { qs.clone().count(conn).await? } else { 0 };
                 let qs = if let Some(offset) = param.offset { qs.offset(offset) } else { qs };
                 let qs = if let Some(limit) = param.limit { qs.limit(limit) } else { qs };
-                let count = qs.clone().count(conn).await?;
                 qs.all(conn).await?
                     .into_iter()
                     .map(|x| #ident::convert_to_json_for_list(x, &fields))
@@ -526,8 +873,8 @@ impl ModelAdminExpander {
 
         Ok(quote!(
             impl #ident {
-            async fn get_impl(
-                conn: &seaorm_admin::sea_orm::DatabaseConnection,
+            async fn get_impl<C: seaorm_admin::sea_orm::ConnectionTrait>(
+                conn: &C,
                 cond: &seaorm_admin::sea_orm::Condition
             ) -> seaorm_admin::Result<Option<seaorm_admin::Json>> {
                 use seaorm_admin::sea_orm::{EntityTrait, QueryFilter};
@@ -545,9 +892,23 @@ impl ModelAdminExpander {
         ))
     }
 
+    /// Runs the `validate` function on the populated `model` before it is written.
+    fn expand_validate(&self, operation: TokenStream) -> TokenStream {
+        if let Some(validate) = &self.validate {
+            quote!(
+                #validate(&model, conn, seaorm_admin::SaveOperation::#operation)
+                    .await?
+                    .into_result()?;
+            )
+        } else {
+            quote!()
+        }
+    }
+
     fn expand_insert_impl(&self) -> Result {
         let ident = &self.ident;
         let module = &self.module;
+        let validate = self.expand_validate(quote!(Create));
 
         Ok(quote!(
             impl #ident {
@@ -555,11 +916,12 @@ impl ModelAdminExpander {
                     conn: &seaorm_admin::sea_orm::DatabaseTransaction,
                     value: &seaorm_admin::Json
                 ) -> seaorm_admin::Result<seaorm_admin::Json> {
-                    use seaorm_admin::sea_orm::{EntityTrait, ActiveModelTrait, TryIntoModel};
+                    use seaorm_admin::sea_orm::{EntityTrait, ActiveModelTrait, ConnectionTrait, TryIntoModel};
 
                     let fields = #ident::get_fields();
                     let mut model = #ident::get_initial_value();
-                    seaorm_admin::set_from_json(&mut model, &fields, value)?;
+                    seaorm_admin::set_from_json_for_backend(&mut model, &fields, value, conn.get_database_backend())?;
+                    #validate
                     let saved: #module::Model = model.insert(conn).await?.try_into_model()?;
                     seaorm_admin::to_json(&saved, &fields)
                 }
@@ -570,6 +932,7 @@ impl ModelAdminExpander {
     fn expand_update_impl(&self) -> Result {
         let ident = &self.ident;
         let module = &self.module;
+        let validate = self.expand_validate(quote!(Update));
 
         Ok(quote!(
             impl #ident {
@@ -577,11 +940,12 @@ impl ModelAdminExpander {
                     conn: &seaorm_admin::sea_orm::DatabaseTransaction,
                     value: &seaorm_admin::Json
                 ) -> seaorm_admin::Result<seaorm_admin::Json> {
-                    use seaorm_admin::sea_orm::{TryIntoModel, ActiveModelTrait, EntityTrait};
+                    use seaorm_admin::sea_orm::{TryIntoModel, ActiveModelTrait, ConnectionTrait, EntityTrait};
 
                     let fields = #ident::get_fields();
                     let mut model = #module::ActiveModel { ..Default::default() };
-                    seaorm_admin::set_from_json(&mut model, &fields, value)?;
+                    seaorm_admin::set_from_json_for_backend(&mut model, &#ident::get_updatable_fields(), value, conn.get_database_backend())?;
+                    #validate
                     let saved: #module::Model = model.save(conn).await?.try_into_model().unwrap();
                     seaorm_admin::to_json(&saved, &fields)
                 }
@@ -622,6 +986,7 @@ impl ModelAdminExpander {
             self.expand_get_ordering()?,
             self.expand_get_fields()?,
             self.expand_get_editable_fields()?,
+            self.expand_get_readonly_fields()?,
             self.expand_get_keys()?,
             self.expand_get_search_fields()?,
             self.expand_get_list_per_page()?,
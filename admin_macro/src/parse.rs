@@ -113,10 +113,18 @@ pub fn parse_auto_complete(ident: &Ident, nv: &MetaNameValue) -> Result<Vec<Iden
         .collect::<Result<Vec<_>>>()
 }
 
+pub fn parse_list_filter(ident: &Ident, nv: &MetaNameValue) -> Result<Vec<Expr>> {
+    parse_list_expr(ident, nv, "list_filter must be array")
+}
+
 pub fn parse_search_fields(ident: &Ident, nv: &MetaNameValue) -> Result<Vec<Expr>> {
     parse_list_expr(ident, nv, "search_fields must be array")
 }
 
+pub fn parse_readonly_fields(ident: &Ident, nv: &MetaNameValue) -> Result<Vec<Expr>> {
+    parse_list_expr(ident, nv, "readonly_fields must be array")
+}
+
 pub fn parse_format<'a>(ident: &'a Ident, nv: &'a MetaNameValue) -> Result<&'a Ident> {
     parse_path_ident(ident, nv, "format must be ident")
 }
@@ -125,6 +133,65 @@ pub fn parse_initial_value<'a>(ident: &'a Ident, nv: &'a MetaNameValue) -> Resul
     parse_path_ident(ident, nv, "initial_value must be ident")
 }
 
+pub fn parse_permission<'a>(ident: &'a Ident, nv: &'a MetaNameValue) -> Result<&'a Ident> {
+    parse_path_ident(ident, nv, "permission must be ident")
+}
+
+pub fn parse_validate<'a>(ident: &'a Ident, nv: &'a MetaNameValue) -> Result<&'a Ident> {
+    parse_path_ident(ident, nv, "validate must be ident")
+}
+
+/// `cursor` or `offset`, returned as the `Pagination` variant.
+pub fn parse_pagination(ident: &Ident, nv: &MetaNameValue) -> Result<Ident> {
+    let value = parse_path_ident(ident, nv, "pagination must be cursor or offset")?;
+    match value.to_string().as_str() {
+        "cursor" => Ok(Ident::new("Cursor", value.span())),
+        "offset" => Ok(Ident::new("Offset", value.span())),
+        _ => Err(syn::Error::new(
+            value.span(),
+            "pagination must be cursor or offset",
+        )),
+    }
+}
+
+/// `contains` or `full_text`, returned as the `SearchMode` variant.
+pub fn parse_search_mode(ident: &Ident, nv: &MetaNameValue) -> Result<Ident> {
+    let value = parse_path_ident(ident, nv, "search_mode must be contains or full_text")?;
+    match value.to_string().as_str() {
+        "contains" => Ok(Ident::new("Contains", value.span())),
+        "full_text" => Ok(Ident::new("FullText", value.span())),
+        _ => Err(syn::Error::new(
+            value.span(),
+            "search_mode must be contains or full_text",
+        )),
+    }
+}
+
+/// `exact`, `estimated` or `none`, returned as the `CountStrategy` variant.
+pub fn parse_count_strategy(ident: &Ident, nv: &MetaNameValue) -> Result<Ident> {
+    let value = parse_path_ident(ident, nv, "count_strategy must be exact, estimated or none")?;
+    match value.to_string().as_str() {
+        "exact" => Ok(Ident::new("Exact", value.span())),
+        "estimated" => Ok(Ident::new("Estimated", value.span())),
+        "none" => Ok(Ident::new("None", value.span())),
+        _ => Err(syn::Error::new(
+            value.span(),
+            "count_strategy must be exact, estimated or none",
+        )),
+    }
+}
+
+pub fn parse_hook<'a>(ident: &'a Ident, nv: &'a MetaNameValue) -> Result<&'a Ident> {
+    parse_path_ident(ident, nv, "hook must be ident")
+}
+
+pub fn parse_actions(ident: &Ident, nv: &MetaNameValue) -> Result<Vec<Ident>> {
+    parse_list_expr(ident, nv, "actions must be array")?
+        .iter()
+        .map(|x| parse_path_ident_from_expr(ident, x, "actions element must be ident").cloned())
+        .collect::<Result<Vec<_>>>()
+}
+
 pub fn parse_ordering(ident: &Ident, nv: &MetaNameValue) -> Result<Vec<(Expr, Expr)>> {
     match &nv.value {
         syn::Expr::Array(a) => a
@@ -151,6 +218,59 @@ pub fn parse_ordering(ident: &Ident, nv: &MetaNameValue) -> Result<Vec<(Expr, Ex
     }
 }
 
+/// `(title, [Column | "relation name", ..])` or `(title, [..], collapsed)`
+pub struct FieldsetExpr {
+    pub title: syn::LitStr,
+    pub fields: Vec<IdentOrLiteral>,
+    pub collapsed: bool,
+}
+
+pub fn parse_fieldsets(ident: &Ident, nv: &MetaNameValue) -> Result<Vec<FieldsetExpr>> {
+    let error_message =
+        "fieldsets must be [(\"title\", [Column | \"relation name\", ..], collapsed?), ..]";
+    let error = || syn::Error::new(ident.span(), error_message);
+    match &nv.value {
+        syn::Expr::Array(a) => a
+            .elems
+            .iter()
+            .map(|x| match x {
+                syn::Expr::Tuple(t) if t.elems.len() == 2 || t.elems.len() == 3 => {
+                    let title = match &t.elems[0] {
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(s), ..
+                        }) => s.clone(),
+                        _ => return Err(error()),
+                    };
+                    let fields = match &t.elems[1] {
+                        Expr::Array(a) => a
+                            .elems
+                            .iter()
+                            .map(|x| parse_ident_or_literal(x, ident.span(), error_message))
+                            .collect::<Result<Vec<_>>>()?,
+                        _ => return Err(error()),
+                    };
+                    let collapsed = match t.elems.get(2) {
+                        None => false,
+                        Some(e) => {
+                            if parse_path_ident_from_expr(ident, e, error_message)? != "collapsed" {
+                                return Err(error());
+                            }
+                            true
+                        }
+                    };
+                    Ok(FieldsetExpr {
+                        title,
+                        fields,
+                        collapsed,
+                    })
+                }
+                _ => Err(error()),
+            })
+            .collect::<Result<Vec<_>>>(),
+        _ => Err(error()),
+    }
+}
+
 pub fn parse_form_fields(ident: &Ident, nv: &MetaNameValue) -> Result<Vec<Expr>> {
     match &nv.value {
         syn::Expr::Array(a) => Ok(a.elems.iter().map(|x| x.clone()).collect::<Vec<_>>()),
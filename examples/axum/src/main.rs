@@ -1,7 +1,9 @@
 use axum::{extract::Extension, Router};
 use entity::{author, post, tag, tag_relation, test_model};
 use sea_orm::Set;
-use seaorm_admin::{enum_field, inline_field, m2m_field, AdminBuilder, ModelAdmin};
+use seaorm_admin::{
+    enum_field, inline_field, m2m_field, AdminBuilder, AuditLogAdmin, ModelAdmin, SeaOrmAuditLog,
+};
 use std::net::SocketAddr;
 use std::sync::Arc;
 
@@ -78,6 +80,8 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error + Send + Sy
         .add_model(TestAdmin)
         .add_model(TagAdmin)
         .add_model(TagRelationAdmin)
+        .add_model(AuditLogAdmin)
+        .audit_log(SeaOrmAuditLog)
         .build(connection, "/admin")?;
 
     let app = Router::new()
@@ -4,6 +4,7 @@ mod m20220101_000001_create_table;
 mod m20230421_000833_add_test;
 mod m20230628_051557_add_tag;
 mod m20230629_020856_add_unique;
+mod m20240101_000000_add_admin_log;
 
 pub struct Migrator;
 
@@ -15,6 +16,7 @@ impl MigratorTrait for Migrator {
             Box::new(m20230421_000833_add_test::Migration),
             Box::new(m20230628_051557_add_tag::Migration),
             Box::new(m20230629_020856_add_unique::Migration),
+            Box::new(m20240101_000000_add_admin_log::Migration),
         ]
     }
 }
@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AdminLog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AdminLog::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AdminLog::ActionTime)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AdminLog::Actor).string().not_null())
+                    .col(ColumnDef::new(AdminLog::TableName).string().not_null())
+                    .col(ColumnDef::new(AdminLog::ObjectKey).string().not_null())
+                    .col(ColumnDef::new(AdminLog::Action).string().not_null())
+                    .col(ColumnDef::new(AdminLog::Before).json_binary())
+                    .col(ColumnDef::new(AdminLog::After).json_binary())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .table(AdminLog::Table)
+                    .name("admin_log_object")
+                    .col(AdminLog::TableName)
+                    .col(AdminLog::ObjectKey)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AdminLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AdminLog {
+    Table,
+    Id,
+    ActionTime,
+    Actor,
+    TableName,
+    ObjectKey,
+    Action,
+    Before,
+    After,
+}
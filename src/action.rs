@@ -0,0 +1,41 @@
+use crate::{Json, Result};
+use sea_orm::DatabaseTransaction;
+use std::{future::Future, pin::Pin};
+
+pub type ActionFuture<'a> = Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+/// Receives the selected rows and returns the message shown on the list page.
+pub type ActionHandler = for<'a> fn(&'a DatabaseTransaction, Vec<Json>) -> ActionFuture<'a>;
+
+/// An operation on the rows selected on the list page, e.g. "mark as published".
+///
+/// ```ignore
+/// fn publish<'a>(txn: &'a DatabaseTransaction, rows: Vec<Json>) -> ActionFuture<'a> {
+///     Box::pin(async move { Ok(format!("{} posts published", rows.len())) })
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AdminAction {
+    pub name: String,
+    pub label: String,
+    pub handler: ActionHandler,
+}
+
+impl AdminAction {
+    pub fn new(name: &str, handler: ActionHandler) -> Self {
+        AdminAction {
+            name: name.into(),
+            label: name.replace('_', " "),
+            handler,
+        }
+    }
+
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    pub async fn run(&self, txn: &DatabaseTransaction, rows: Vec<Json>) -> Result<String> {
+        (self.handler)(txn, rows).await
+    }
+}
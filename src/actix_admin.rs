@@ -0,0 +1,65 @@
+use super::{Admin, AdminBody, AdminRequest, AdminResponse, CSRF_HEADER_NAME, MAX_BODY_SIZE};
+use actix_web::{
+    body::BoxBody,
+    dev::{ServiceFactory, ServiceRequest, ServiceResponse},
+    http::{
+        header::{self, ContentType},
+        StatusCode,
+    },
+    web, HttpRequest, HttpResponse, Responder, Scope,
+};
+use futures_util::TryStreamExt;
+
+impl Responder for AdminResponse {
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut builder = HttpResponse::build(status);
+        for (name, value) in self.headers {
+            builder.append_header((name, value));
+        }
+        match self.body {
+            AdminBody::Empty => builder.finish(),
+            AdminBody::Html(html) => builder.content_type(ContentType::html()).body(html),
+            AdminBody::Json(value) => builder.json(value),
+            AdminBody::Stream(stream) => builder.streaming(stream.map_ok(web::Bytes::from)),
+        }
+    }
+}
+
+async fn handle(admin: web::Data<Admin>, req: HttpRequest, body: web::Bytes) -> AdminResponse {
+    let mut request = AdminRequest::new(req.method().as_str(), req.path())
+        .query(Some(req.query_string()))
+        .body(body.to_vec());
+    for name in [
+        header::ACCEPT.as_str(),
+        header::AUTHORIZATION.as_str(),
+        header::CONTENT_TYPE.as_str(),
+        header::COOKIE.as_str(),
+        CSRF_HEADER_NAME,
+    ] {
+        if let Some(value) = req.headers().get(name).and_then(|x| x.to_str().ok()) {
+            request = request.header(name, value);
+        }
+    }
+    admin.into_inner().handle(request).await
+}
+
+/// The admin routes mounted at `path`, usually `admin.sub_path()`.
+/// The `Admin` itself is read from `web::Data<Admin>`, register it with `App::app_data`.
+pub fn get_scope(
+    path: &str,
+) -> Scope<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<BoxBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    web::scope(path)
+        .app_data(web::PayloadConfig::new(MAX_BODY_SIZE))
+        .default_service(web::to(handle))
+}
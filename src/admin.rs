@@ -1,17 +1,31 @@
-use crate::{create_cond_from_json, json_overwrite_key, list_query_to_list_param};
+use crate::{
+    create_cond_from_key, delete_is_blocked, delete_is_unchecked, json_force_str,
+    json_overwrite_key, list_query_to_list_param, model_cursor_ordering, reverse_ordering,
+    seek_condition, CountStrategy, Cursor, DeleteEffect, ListParam, ListQuery, ListTotal,
+    Pagination, RelatedRows,
+};
 
-use super::{templates, AdminField, Json, ModelAdminTrait, Result};
+use super::{
+    display_columns, templates, AdminAuth, AdminError, AdminField, AdminUser, AuditAction,
+    AuditEntry, AuditLog, AuthRequest, ImportDecision, ImportResult, ImportRow, Json,
+    ModelAdminTrait, Result, SaveOperation,
+};
 use askama::DynTemplate;
-use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+use sea_orm::{
+    Condition, ConnectionTrait, DatabaseConnection, DatabaseTransaction, TransactionTrait,
+};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     ops::Deref,
 };
 
+/// Rows listed on the bulk delete page when all matching rows are selected.
+const BULK_ROWS_SHOWN: u64 = 20;
+
 fn to_query_string(params: &HashMap<String, Vec<String>>, page: u64) -> String {
     let mut query = String::new();
     for (key, values) in params.iter() {
-        if key == "_p" {
+        if key == "_p" || key == "_msg" {
             continue;
         }
         for value in values {
@@ -48,18 +62,62 @@ where
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 enum FormType {
     CREATE,
     UPDATE,
     DELETE,
+    READONLY,
+}
+
+struct ListPage {
+    total: ListTotal,
+    rows: Vec<Json>,
+    has_next: bool,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
 }
 
 pub struct Admin {
     conn: Box<dyn Connector + Sync + Send>,
+    auth: Option<Box<dyn AdminAuth + Send + Sync>>,
+    audit_log: Option<Box<dyn AuditLog + Send + Sync>>,
     pub models: HashMap<String, Box<dyn ModelAdminTrait + Send + Sync>>,
     pub site: templates::AdminSite,
 }
 
+/// Groups `(names, template)` by the fieldsets of `model`, in their order.
+/// Templates no fieldset lists follow in an untitled group.
+fn group_fieldsets(
+    model: &(dyn ModelAdminTrait + Send + Sync),
+    mut templates: Vec<(Vec<String>, Box<dyn DynTemplate + Send>)>,
+) -> Vec<templates::FormFieldset> {
+    let mut fieldsets = Vec::new();
+    for fieldset in model.get_fieldsets() {
+        let mut fields = Vec::new();
+        for name in fieldset.fields.iter() {
+            if let Some(i) = templates.iter().position(|(names, _)| names.contains(name)) {
+                fields.push(templates.remove(i).1);
+            }
+        }
+        if !fields.is_empty() {
+            fieldsets.push(templates::FormFieldset {
+                title: Some(fieldset.title),
+                collapsed: fieldset.collapsed,
+                fields,
+            });
+        }
+    }
+    if !templates.is_empty() {
+        fieldsets.push(templates::FormFieldset {
+            title: None,
+            collapsed: false,
+            fields: templates.into_iter().map(|(_, x)| x).collect(),
+        });
+    }
+    fieldsets
+}
+
 impl Admin {
     pub fn new<C>(conn: C, sub_path: &str) -> Self
     where
@@ -67,10 +125,13 @@ impl Admin {
     {
         Admin {
             conn: Box::new(ConnectorImpl { conn }),
+            auth: None,
+            audit_log: None,
             models: HashMap::new(),
             site: templates::AdminSite {
                 title: "Admin".into(),
                 models: Vec::new(),
+                addable_models: HashSet::new(),
                 sub_path: sub_path.trim_end_matches('/').to_string(),
             },
         }
@@ -84,6 +145,75 @@ impl Admin {
         &self.site.sub_path
     }
 
+    pub fn set_auth<A>(&mut self, auth: A) -> &Self
+    where
+        A: AdminAuth + Send + Sync + 'static,
+    {
+        self.auth = Some(Box::new(auth));
+        self
+    }
+
+    /// Whether an `AdminAuth` is configured.
+    pub fn has_auth(&self) -> bool {
+        self.auth.is_some()
+    }
+
+    /// Resolves the user of the request. Without an `AdminAuth` every request is
+    /// treated as a superuser, so the admin stays open as before.
+    pub async fn authenticate(&self, request: &AuthRequest) -> Result<Option<AdminUser>> {
+        match &self.auth {
+            Some(auth) => auth.authenticate(self.get_connection(), request).await,
+            None => Ok(Some(AdminUser::superuser("admin"))),
+        }
+    }
+
+    pub fn set_audit_log<L>(&mut self, audit_log: L) -> &Self
+    where
+        L: AuditLog + Send + Sync + 'static,
+    {
+        self.audit_log = Some(Box::new(audit_log));
+        self
+    }
+
+    /// Whether changes are recorded, callers skip reading the `before` row without it.
+    pub fn has_audit_log(&self) -> bool {
+        self.audit_log.is_some()
+    }
+
+    /// Records a change made by `user` when an `AuditLog` is configured.
+    pub async fn audit(
+        &self,
+        model: &Box<dyn ModelAdminTrait + Send + Sync>,
+        action: AuditAction,
+        user: &AdminUser,
+        before: Option<Json>,
+        after: Option<Json>,
+        txn: &DatabaseTransaction,
+    ) -> Result<()> {
+        let audit_log = match &self.audit_log {
+            Some(audit_log) => audit_log,
+            None => return Ok(()),
+        };
+        let object_key = match after.as_ref().or(before.as_ref()) {
+            Some(row) => model.json_to_key(row)?,
+            None => String::new(),
+        };
+        let entry = AuditEntry {
+            table_name: model.get_table_name().into(),
+            object_key,
+            action,
+            actor: user.username.clone(),
+            timestamp: std::time::SystemTime::now(),
+            before,
+            after,
+        };
+        audit_log.record(txn, &entry).await
+    }
+
+    pub fn login_url(&self) -> Option<String> {
+        self.auth.as_ref().and_then(|x| x.login_url())
+    }
+
     pub fn add_model<T>(&mut self, model_admin: T) -> &Self
     where
         T: ModelAdminTrait + Send + Sync + 'static,
@@ -91,35 +221,154 @@ impl Admin {
         let table_name: String = model_admin.get_table_name().into();
         self.models
             .insert(table_name.clone(), Box::new(model_admin));
-        self.site.models.push(table_name);
+        self.site.models.push(table_name.clone());
+        self.site.addable_models.insert(table_name);
         self
     }
 
+    /// The site as seen by `user`, models without view permission are dropped.
+    pub fn get_site(&self, user: &AdminUser) -> templates::AdminSite {
+        let mut site = self.site.clone();
+        let model = |x: &String| self.models.get(x);
+        site.models.retain(|x| {
+            model(x)
+                .map(|m| m.has_view_permission(user))
+                .unwrap_or(false)
+        });
+        site.addable_models.retain(|x| {
+            model(x)
+                .map(|m| m.has_add_permission(user))
+                .unwrap_or(false)
+        });
+        site
+    }
+
     pub fn get_model(&self, table_name: &str) -> Option<&Box<dyn ModelAdminTrait + Send + Sync>> {
         self.models.get(table_name)
     }
 
+    /// Number of rows matching `cond` as found by the count strategy of the model.
+    pub(crate) async fn list_total(
+        &self,
+        model: &(dyn ModelAdminTrait + Send + Sync),
+        cond: &Condition,
+    ) -> Result<ListTotal> {
+        let conn = self.get_connection();
+        Ok(match model.get_count_strategy() {
+            CountStrategy::Exact => ListTotal::Exact(model.count(conn, cond).await?),
+            CountStrategy::Estimated => match model.estimate_count(conn, cond).await? {
+                Some(x) => ListTotal::Estimated(x),
+                None => ListTotal::Exact(model.count(conn, cond).await?),
+            },
+            CountStrategy::None => ListTotal::Unknown,
+        })
+    }
+
+    /// Rows of one page of the list. Cursor paginated models seek from `query.cursor`
+    /// and return the cursors of the neighbouring pages, if there are any.
+    async fn list_page(
+        &self,
+        model: &(dyn ModelAdminTrait + Send + Sync),
+        query: &ListQuery,
+    ) -> Result<ListPage> {
+        let columns = model.get_columns();
+        let mut param =
+            list_query_to_list_param(query, model, self.get_connection().get_database_backend())?;
+        let total = self.list_total(model, &param.cond).await?;
+        // one more row tells whether there is a page after this one
+        param.limit = Some(query.limit + 1);
+        if model.get_pagination() == Pagination::Offset {
+            let (_, mut rows) = model.list(self.get_connection(), &param).await?;
+            let has_next = rows.len() as u64 > query.limit;
+            rows.truncate(query.limit as usize);
+            return Ok(ListPage {
+                total,
+                rows,
+                has_next,
+                next_cursor: None,
+                prev_cursor: None,
+            });
+        }
+
+        let ordering = model_cursor_ordering(model, &query.ordering);
+
+        let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
+        let backward = cursor.as_ref().map(|x| x.backward).unwrap_or(false);
+        if let Some(cursor) = &cursor {
+            param.cond = param.cond.add(seek_condition(
+                &ordering,
+                &columns,
+                cursor,
+                self.get_connection().get_database_backend(),
+            )?);
+        }
+        param.ordering = if backward {
+            reverse_ordering(&ordering)
+        } else {
+            ordering.clone()
+        };
+        param.offset = None;
+        // rows are positioned by their column values, relevance can not be seeked from
+        param.rank = None;
+        let (_, mut rows) = model.list(self.get_connection(), &param).await?;
+        let has_more = rows.len() as u64 > query.limit;
+        rows.truncate(query.limit as usize);
+        if backward {
+            rows.reverse();
+        }
+
+        let (has_next, has_prev) = if backward {
+            (true, has_more)
+        } else {
+            (has_more, cursor.is_some())
+        };
+        let next_cursor = rows
+            .last()
+            .filter(|_| has_next)
+            .map(|x| Cursor::from_row(x, &ordering, false).encode());
+        let prev_cursor = rows
+            .first()
+            .filter(|_| has_prev)
+            .map(|x| Cursor::from_row(x, &ordering, true).encode());
+        Ok(ListPage {
+            total,
+            rows,
+            has_next,
+            next_cursor,
+            prev_cursor,
+        })
+    }
+
     pub async fn get_list_as_json(
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
         query_param: &HashMap<String, Vec<String>>,
     ) -> Result<Json> {
         let query = super::parse_query(query_param, model.get_list_per_page())?;
-        let param = list_query_to_list_param(&query, &model.get_columns())?;
-        let (total, object_list) = model.list(&self.get_connection(), &param).await?;
-        super::json_convert_vec_to_json(model, total, object_list)
+        let page = self.list_page(model.as_ref(), &query).await?;
+        let mut json = super::json_convert_vec_to_json(model, page.total, page.rows)?;
+        json["has_next"] = Json::Bool(page.has_next);
+        json["next_cursor"] = serde_json::json!(page.next_cursor);
+        json["prev_cursor"] = serde_json::json!(page.prev_cursor);
+        Ok(json)
     }
 
     pub async fn get_list_template(
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
         query_param: &HashMap<String, Vec<String>>,
+        user: &AdminUser,
+        csrf_token: &str,
     ) -> Result<templates::AdminList> {
         let query = super::parse_query(query_param, model.get_list_per_page())?;
-        let param = list_query_to_list_param(&query, &model.get_columns())?;
-        let (count, object_list) = model.list(&self.get_connection(), &param).await?;
+        let page = self.list_page(model.as_ref(), &query).await?;
+        let object_list = page.rows;
         let list_per_page = model.get_list_per_page();
-        let num_pages = (count + list_per_page - 1) / list_per_page;
+        let num_pages = page
+            .total
+            .value()
+            .map(|x| x.div_ceil(list_per_page))
+            .unwrap_or(0);
         let current_page = query.offset / list_per_page;
         let min_page = std::cmp::max(current_page as i64 - 3, 0) as u64;
         let max_page = std::cmp::min(current_page + 3, num_pages);
@@ -151,16 +400,78 @@ impl Admin {
             pages.push(get_page(num_pages - 1));
         }
 
+        let cursor_link = |cursor: String| {
+            crate::facet::to_link(
+                &query_param
+                    .iter()
+                    .filter(|(k, _)| !matches!(k.as_str(), "_p" | "_c" | "_msg"))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+                &[("_c".to_string(), cursor)],
+            )
+        };
+
+        // without a total the offset pages only link to their neighbours
+        let adjacent_links = if model.get_pagination() == Pagination::Offset {
+            if page.total == ListTotal::Unknown {
+                (
+                    (current_page > 0)
+                        .then(|| format!("?{}", to_query_string(query_param, current_page - 1))),
+                    page.has_next
+                        .then(|| format!("?{}", to_query_string(query_param, current_page + 1))),
+                )
+            } else {
+                (None, None)
+            }
+        } else {
+            (
+                page.prev_cursor.map(&cursor_link),
+                page.next_cursor.map(&cursor_link),
+            )
+        };
+
         let keys = model.list_display();
+        let row_page = if model.has_change_permission(user) {
+            "update"
+        } else {
+            "view"
+        };
         Ok(templates::AdminList {
-            site: self.site.clone(),
+            site: self.get_site(user),
             model_name: model.get_table_name().into(),
+            can_add: model.has_add_permission(user),
+            can_delete: model.has_delete_permission(user),
+            csrf_token: csrf_token.into(),
+            actions: if model.has_change_permission(user) {
+                model
+                    .get_actions()
+                    .into_iter()
+                    .map(|x| (x.name, x.label))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            message: query_param.get("_msg").and_then(|x| x.first()).cloned(),
+            filters: super::get_list_filters(self, user, model.as_ref(), query_param).await?,
+            export_link: format!(
+                "export/{}",
+                crate::facet::to_link(
+                    &query_param
+                        .iter()
+                        .filter(|(k, _)| *k != "_p" && *k != "_msg")
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),
+                    &[]
+                )
+            ),
             keys: keys.iter().cloned().collect(),
             rows: object_list
                 .iter()
                 .map(|x| {
+                    let key = model.json_to_key(x)?;
                     Ok((
-                        format!("update/{}/", model.json_to_key(x)?),
+                        format!("{}/{}/", row_page, key),
+                        key,
                         keys.iter()
                             .map(|key| super::json_force_str(x.get(key).unwrap()))
                             .collect(),
@@ -168,25 +479,35 @@ impl Admin {
                 })
                 .collect::<Result<Vec<_>>>()?,
             query: query.clone(),
-            pages: pages,
-            total: count,
+            pages: if model.get_pagination() == Pagination::Offset {
+                pages
+            } else {
+                Vec::new()
+            },
+            adjacent_links,
+            total: page.total,
         })
     }
 
+    /// Rendered fields of the form grouped by the fieldsets of `model`,
+    /// fields no fieldset lists follow in an untitled group.
     async fn get_form_fields(
         &self,
-        base_fields: Vec<AdminField>,
+        model: &(dyn ModelAdminTrait + Send + Sync),
         row: Option<&Json>,
-        primary_keys: Vec<String>,
         form_type: FormType,
-    ) -> Result<Vec<Box<dyn DynTemplate + Send>>> {
+    ) -> Result<Vec<templates::FormFieldset>> {
         let mut templates = Vec::new();
-        let primary_keys: HashSet<String> = primary_keys.into_iter().collect();
-        for field in base_fields.iter() {
+        let primary_keys: HashSet<String> = model.get_primary_keys().into_iter().collect();
+        let readonly_fields: HashSet<String> = model.get_readonly_fields().into_iter().collect();
+        for field in model.get_form_fields().iter() {
             let disabled = match form_type {
                 FormType::CREATE => match field {
                     AdminField::Field(f) => {
-                        if f.fields().into_iter().any(|x| primary_keys.contains(&x)) {
+                        if f.fields()
+                            .into_iter()
+                            .any(|x| primary_keys.contains(&x) || readonly_fields.contains(&x))
+                        {
                             continue;
                         }
                         false
@@ -194,37 +515,69 @@ impl Admin {
                     _ => false,
                 },
                 FormType::UPDATE => match field {
-                    AdminField::Field(f) => {
-                        f.fields().into_iter().any(|x| primary_keys.contains(&x))
-                    }
+                    AdminField::Field(f) => f
+                        .fields()
+                        .into_iter()
+                        .any(|x| primary_keys.contains(&x) || readonly_fields.contains(&x)),
                     _ => false,
                 },
-                FormType::DELETE => true,
+                FormType::DELETE | FormType::READONLY => true,
             };
             let r = field.get_template(self, row, "", disabled).await?;
-            templates.push(r);
+            templates.push((field.names(), r));
         }
-        Ok(templates)
+        Ok(group_fieldsets(model, templates))
+    }
+
+    /// Read-only page of `row`, fields are rendered with `AdminField::get_display`
+    /// and columns without a field follow as text.
+    pub async fn get_detail_template(
+        &self,
+        model: &(dyn ModelAdminTrait + Send + Sync),
+        row: &Json,
+        user: &AdminUser,
+    ) -> Result<templates::AdminDetail> {
+        let mut templates = Vec::new();
+        let mut shown = HashSet::new();
+        for field in model.get_form_fields() {
+            shown.extend(field.names());
+            templates.push((field.names(), field.get_display(self, row, user).await?));
+        }
+        for (name, _) in model.get_columns() {
+            if !shown.contains(&name) {
+                let columns = vec![name];
+                let template = display_columns(&columns, row);
+                templates.push((columns, template));
+            }
+        }
+
+        Ok(templates::AdminDetail {
+            site: self.get_site(user),
+            page_id: model.json_to_key(row)?,
+            model_name: model.get_table_name().into(),
+            title: model.to_str(row)?,
+            fields: group_fieldsets(model, templates),
+            can_change: model.has_change_permission(user),
+            can_delete: model.has_delete_permission(user),
+        })
     }
 
     pub async fn get_create_template(
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
+        user: &AdminUser,
+        csrf_token: &str,
     ) -> Result<templates::AdminCreateForm> {
         Ok(templates::AdminCreateForm {
-            site: self.site.clone(),
+            site: self.get_site(user),
+            csrf_token: csrf_token.into(),
             form_id: format!("{}-create", model.get_table_name()),
             page_id: "create".into(),
             model_name: model.get_table_name().into(),
             action: None,
             method: "POST".into(),
             fields: self
-                .get_form_fields(
-                    model.get_form_fields(),
-                    None,
-                    model.get_primary_keys(),
-                    FormType::CREATE,
-                )
+                .get_form_fields(model.as_ref(), None, FormType::CREATE)
                 .await?,
         })
     }
@@ -233,11 +586,15 @@ impl Admin {
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
         row: &Json,
+        user: &AdminUser,
+        csrf_token: &str,
     ) -> Result<templates::AdminUpdateForm> {
         let id = model.json_to_key(row)?;
+        let can_change = model.has_change_permission(user);
 
         Ok(templates::AdminUpdateForm {
-            site: self.site.clone(),
+            site: self.get_site(user),
+            csrf_token: csrf_token.into(),
             form_id: format!("{}-update", model.get_table_name()),
             page_id: id,
             model_name: model.get_table_name().into(),
@@ -245,12 +602,17 @@ impl Admin {
             method: "POST".into(),
             fields: self
                 .get_form_fields(
-                    model.get_form_fields(),
+                    model.as_ref(),
                     Some(row),
-                    model.get_primary_keys(),
-                    FormType::UPDATE,
+                    if can_change {
+                        FormType::UPDATE
+                    } else {
+                        FormType::READONLY
+                    },
                 )
                 .await?,
+            can_change,
+            can_delete: model.has_delete_permission(user),
         })
     }
 
@@ -258,11 +620,12 @@ impl Admin {
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
         data: &Json,
+        user: &AdminUser,
         txn: &DatabaseTransaction,
     ) -> Result<()> {
         for field in model.get_form_fields() {
             match field {
-                AdminField::Relation(rel) => rel.commit(self, data, txn).await?,
+                AdminField::Relation(rel) => rel.commit(self, data, user, txn).await?,
                 _ => Json::Null,
             };
         }
@@ -273,6 +636,7 @@ impl Admin {
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
         data: &Json,
+        user: &AdminUser,
         txn: Option<&DatabaseTransaction>,
     ) -> Result<Json> {
         let internal_txn = if txn.is_none() {
@@ -287,12 +651,18 @@ impl Admin {
             internal_txn.as_ref().unwrap()
         };
 
-        // let txn = self.get_connection().begin().await?;
+        let data = model
+            .before_save(cur_txn, data.clone(), user, SaveOperation::Create)
+            .await?;
+        let r = model.insert(cur_txn, &data).await?;
+        let data = json_overwrite_key(&data, &r)?;
 
-        let r = model.insert(&cur_txn, data).await?;
-        let data = json_overwrite_key(data, &r)?;
-
-        self.handle_relation(model, &data, cur_txn).await?;
+        self.handle_relation(model, &data, user, cur_txn).await?;
+        model
+            .after_save(cur_txn, &r, user, SaveOperation::Create)
+            .await?;
+        self.audit(model, AuditAction::Create, user, None, Some(r), cur_txn)
+            .await?;
 
         if let Some(txn_data) = internal_txn {
             txn_data.commit().await?;
@@ -305,6 +675,7 @@ impl Admin {
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
         data: &Json,
+        user: &AdminUser,
         txn: Option<&DatabaseTransaction>,
     ) -> Result<Json> {
         let internal_txn = if txn.is_none() {
@@ -319,10 +690,43 @@ impl Admin {
             internal_txn.as_ref().unwrap()
         };
 
-        let r = model.update(cur_txn, data).await?;
-        let data = json_overwrite_key(data, &r)?;
+        let primary_keys = model.get_primary_keys();
+        let readonly_fields: Vec<String> = model
+            .get_readonly_fields()
+            .into_iter()
+            .filter(|x| !primary_keys.contains(x) && data.get(x).is_some())
+            .collect();
+        let before = if self.has_audit_log() || !readonly_fields.is_empty() {
+            let cond = create_cond_from_key(model.as_ref(), data)?;
+            model.get_with_txn(cur_txn, &cond).await?
+        } else {
+            None
+        };
+        // read-only values may be sent back unchanged, changing one is an error
+        if let Some(stored) = &before {
+            let errors: BTreeMap<String, Vec<String>> = readonly_fields
+                .iter()
+                .filter(|x| {
+                    json_force_str(&data[x.as_str()]) != json_force_str(&stored[x.as_str()])
+                })
+                .map(|x| (x.clone(), vec!["read-only".to_string()]))
+                .collect();
+            if !errors.is_empty() {
+                return Err(AdminError::Validation(errors).into());
+            }
+        }
+        let data = model
+            .before_save(cur_txn, data.clone(), user, SaveOperation::Update)
+            .await?;
+        let r = model.update(cur_txn, &data).await?;
+        let data = json_overwrite_key(&data, &r)?;
 
-        self.handle_relation(model, &data, cur_txn).await?;
+        self.handle_relation(model, &data, user, cur_txn).await?;
+        model
+            .after_save(cur_txn, &r, user, SaveOperation::Update)
+            .await?;
+        self.audit(model, AuditAction::Update, user, before, Some(r), cur_txn)
+            .await?;
 
         if let Some(txn_data) = internal_txn {
             txn_data.commit().await?;
@@ -335,6 +739,7 @@ impl Admin {
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
         data: &Json,
+        user: &AdminUser,
         txn: Option<&DatabaseTransaction>,
     ) -> Result<u64> {
         let internal_txn = if txn.is_none() {
@@ -347,45 +752,466 @@ impl Admin {
         } else {
             internal_txn.as_ref().unwrap()
         };
-        let cond = create_cond_from_json(&model.get_primary_keys(), &data, true)?;
+        let cond = create_cond_from_key(model.as_ref(), data)?;
+        let before = if self.has_audit_log() {
+            model.get_with_txn(cur_txn, &cond).await?
+        } else {
+            None
+        };
+        model.before_delete(cur_txn, data, user).await?;
         let resp = model.delete(cur_txn, &cond).await?;
+        if resp > 0 {
+            model.after_delete(cur_txn, data, user).await?;
+            self.audit(model, AuditAction::Delete, user, before, None, cur_txn)
+                .await?;
+        }
         if let Some(txn_data) = internal_txn {
             txn_data.commit().await?;
         }
         Ok(resp)
     }
 
+    /// Condition of the rows matching the list filters of `query_param`, as selected with `_all`.
+    fn bulk_matching_cond(
+        &self,
+        model: &(dyn ModelAdminTrait + Send + Sync),
+        query_param: &HashMap<String, Vec<String>>,
+    ) -> Result<Condition> {
+        let query = super::parse_query(query_param, model.get_list_per_page())?;
+        Ok(
+            list_query_to_list_param(&query, model, self.get_connection().get_database_backend())?
+                .cond,
+        )
+    }
+
+    /// Rows picked on the list page: every `_k` key, or all rows matching the filter when `_all` is set.
+    pub async fn get_bulk_rows(
+        &self,
+        model: &Box<dyn ModelAdminTrait + Send + Sync>,
+        query_param: &HashMap<String, Vec<String>>,
+    ) -> Result<Vec<Json>> {
+        if query_param.contains_key("_all") {
+            let param = ListParam {
+                cond: self.bulk_matching_cond(model.as_ref(), query_param)?,
+                ordering: vec![],
+                offset: None,
+                limit: None,
+                count: false,
+                rank: None,
+            };
+            let (_, rows) = model.list(self.get_connection(), &param).await?;
+            return Ok(rows);
+        }
+
+        let mut rows = Vec::new();
+        for key in query_param.get("_k").into_iter().flatten() {
+            let key = model.key_to_json(key)?;
+            let cond = create_cond_from_key(model.as_ref(), &key)?;
+            if let Some(row) = model.get(self.get_connection(), &cond).await? {
+                rows.push(row);
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Confirmation of a bulk delete. With `_all` the matching rows are counted and only the first
+    /// `BULK_ROWS_SHOWN` are listed, the form sends the filter back instead of the keys.
+    pub async fn get_bulk_delete_template(
+        &self,
+        model: &Box<dyn ModelAdminTrait + Send + Sync>,
+        query_param: &HashMap<String, Vec<String>>,
+        user: &AdminUser,
+        csrf_token: &str,
+    ) -> Result<templates::AdminBulkDeleteForm> {
+        let (rows, total, query) = if query_param.contains_key("_all") {
+            let cond = self.bulk_matching_cond(model.as_ref(), query_param)?;
+            let total = model.count(self.get_connection(), &cond).await?;
+            let param = ListParam {
+                cond,
+                ordering: vec![],
+                offset: None,
+                limit: Some(BULK_ROWS_SHOWN),
+                count: false,
+                rank: None,
+            };
+            let (_, rows) = model.list(self.get_connection(), &param).await?;
+            let filter: HashMap<String, Vec<String>> = query_param
+                .iter()
+                .filter(|(k, _)| !matches!(k.as_str(), "_all" | "_k" | "_p" | "_msg"))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            (rows, total, crate::facet::to_link(&filter, &[]))
+        } else {
+            let rows = self.get_bulk_rows(model, query_param).await?;
+            let total = rows.len() as u64;
+            (rows, total, String::new())
+        };
+        let objects = rows
+            .iter()
+            .map(|x| Ok((model.json_to_key(x)?, model.to_str(x)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(templates::AdminBulkDeleteForm {
+            site: self.get_site(user),
+            csrf_token: csrf_token.into(),
+            form_id: format!("{}-bulk-delete", model.get_table_name()),
+            page_id: "bulk delete".into(),
+            model_name: model.get_table_name().into(),
+            action: None,
+            method: "POST".into(),
+            fields: Vec::new(),
+            keys: if query.is_empty() {
+                objects
+                    .iter()
+                    .map(|(key, _)| key.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            } else {
+                String::new()
+            },
+            query,
+            total,
+            hidden: total - objects.len() as u64,
+            objects,
+        })
+    }
+
+    /// Deletes every key in one transaction, nothing is deleted if one of them fails.
+    pub async fn bulk_delete(
+        &self,
+        model: &Box<dyn ModelAdminTrait + Send + Sync>,
+        keys: &[String],
+        user: &AdminUser,
+        txn: Option<&DatabaseTransaction>,
+    ) -> Result<u64> {
+        let internal_txn = if txn.is_none() {
+            Some(self.conn.get_connection().begin().await?)
+        } else {
+            None
+        };
+        let cur_txn = match txn {
+            Some(txn) => txn,
+            None => internal_txn.as_ref().unwrap(),
+        };
+
+        let mut deleted = 0;
+        for key in keys {
+            let data = model.key_to_json(key)?;
+            deleted += self.delete(model, &data, user, Some(cur_txn)).await?;
+        }
+
+        if let Some(txn_data) = internal_txn {
+            txn_data.commit().await?;
+        }
+        Ok(deleted)
+    }
+
+    /// Deletes every row matching the list filters of `query_param` with one statement.
+    /// The delete hooks and the audit log work on single rows, they are not run for these rows.
+    pub async fn bulk_delete_matching(
+        &self,
+        model: &(dyn ModelAdminTrait + Send + Sync),
+        query_param: &HashMap<String, Vec<String>>,
+        txn: Option<&DatabaseTransaction>,
+    ) -> Result<u64> {
+        let cond = self.bulk_matching_cond(model, query_param)?;
+        let internal_txn = if txn.is_none() {
+            Some(self.conn.get_connection().begin().await?)
+        } else {
+            None
+        };
+        let cur_txn = match txn {
+            Some(txn) => txn,
+            None => internal_txn.as_ref().unwrap(),
+        };
+
+        let deleted = model.delete_many(cur_txn, &cond).await?;
+
+        if let Some(txn_data) = internal_txn {
+            txn_data.commit().await?;
+        }
+        Ok(deleted)
+    }
+
+    pub fn get_import_template(
+        &self,
+        model: &Box<dyn ModelAdminTrait + Send + Sync>,
+        user: &AdminUser,
+        csrf_token: &str,
+    ) -> templates::AdminImportForm {
+        templates::AdminImportForm {
+            site: self.get_site(user),
+            csrf_token: csrf_token.into(),
+            form_id: format!("{}-import", model.get_table_name()),
+            page_id: "import".into(),
+            model_name: model.get_table_name().into(),
+            action: None,
+            method: "POST".into(),
+            fields: Vec::new(),
+            columns: model.get_columns().into_iter().map(|x| x.0).collect(),
+        }
+    }
+
+    /// Creates or updates each row, depending on whether its primary key exists, in one transaction.
+    /// Every row gets a savepoint so all errors are reported, the transaction is only committed
+    /// when no row failed and `dry_run` is false. A dry run still writes the rows to report
+    /// database errors, but skips the `before_save`/`after_save` hooks and the audit log.
+    pub async fn import(
+        &self,
+        model: &Box<dyn ModelAdminTrait + Send + Sync>,
+        rows: Vec<Json>,
+        user: &AdminUser,
+        dry_run: bool,
+    ) -> Result<ImportResult> {
+        let columns: HashSet<String> = model.get_columns().into_iter().map(|x| x.0).collect();
+        let primary_keys = model.get_primary_keys();
+        let mut ignored_columns: Vec<String> = Vec::new();
+        let mut results = Vec::new();
+        let txn = self.conn.get_connection().begin().await?;
+
+        for (i, row) in rows.into_iter().enumerate() {
+            let mut data = serde_json::Map::new();
+            for (key, value) in row.as_object().into_iter().flatten() {
+                if columns.contains(key) {
+                    data.insert(key.clone(), value.clone());
+                } else if !ignored_columns.contains(key) {
+                    ignored_columns.push(key.clone());
+                }
+            }
+            let has_key = primary_keys.iter().all(|k| {
+                data.get(k)
+                    .is_some_and(|v| !v.is_null() && v.as_str() != Some(""))
+            });
+            let existing = if has_key {
+                let key = Json::Object(data.clone());
+                async {
+                    let cond = create_cond_from_key(model.as_ref(), &key)?;
+                    model.get_with_txn(&txn, &cond).await
+                }
+                .await
+            } else {
+                primary_keys.iter().for_each(|k| {
+                    data.remove(k);
+                });
+                Ok(None)
+            };
+            let data = Json::Object(data);
+
+            // a row whose key can not be looked up is reported, it is neither created nor updated
+            let existing = match existing {
+                Ok(existing) => existing,
+                Err(error) => {
+                    results.push(ImportRow {
+                        line: i + 1,
+                        decision: ImportDecision::Error,
+                        key: None,
+                        errors: vec![error.to_string()],
+                    });
+                    continue;
+                }
+            };
+            let mut result = ImportRow {
+                line: i + 1,
+                decision: if existing.is_some() {
+                    ImportDecision::Update
+                } else {
+                    ImportDecision::Create
+                },
+                key: existing.as_ref().and_then(|x| model.json_to_key(x).ok()),
+                errors: Vec::new(),
+            };
+            let allowed = match existing {
+                Some(_) => model.has_change_permission(user),
+                None => model.has_add_permission(user),
+            };
+            if !allowed {
+                result.errors.push("permission denied".into());
+            }
+            if let Err(error) = model.sanitize_json(&data) {
+                result.errors.push(error.to_string());
+            }
+            if result.errors.is_empty() {
+                let savepoint = txn.begin().await?;
+                let (action, operation) = match &existing {
+                    Some(_) => (AuditAction::Update, SaveOperation::Update),
+                    None => (AuditAction::Create, SaveOperation::Create),
+                };
+                let saved = async {
+                    let data = if dry_run {
+                        data
+                    } else {
+                        model.before_save(&savepoint, data, user, operation).await?
+                    };
+                    let r = match operation {
+                        SaveOperation::Update => model.update(&savepoint, &data).await?,
+                        SaveOperation::Create => model.insert(&savepoint, &data).await?,
+                    };
+                    if !dry_run {
+                        model.after_save(&savepoint, &r, user, operation).await?;
+                        self.audit(model, action, user, existing, Some(r.clone()), &savepoint)
+                            .await?;
+                    }
+                    Ok::<_, anyhow::Error>(r)
+                }
+                .await;
+                match saved {
+                    Ok(r) => {
+                        result.key = model.json_to_key(&r).ok();
+                        savepoint.commit().await?;
+                    }
+                    Err(error) => {
+                        result.errors.push(error.to_string());
+                        savepoint.rollback().await?;
+                    }
+                }
+            }
+            results.push(result);
+        }
+
+        let mut result = ImportResult {
+            rows: results,
+            ignored_columns,
+            committed: false,
+        };
+        if dry_run || result.has_errors() {
+            txn.rollback().await?;
+        } else {
+            txn.commit().await?;
+            result.committed = true;
+        }
+        Ok(result)
+    }
+
+    /// Runs the action `name` on `rows` in one transaction and returns its message.
+    pub async fn run_action(
+        &self,
+        model: &Box<dyn ModelAdminTrait + Send + Sync>,
+        name: &str,
+        rows: Vec<Json>,
+        txn: Option<&DatabaseTransaction>,
+    ) -> Result<String> {
+        let action = model
+            .get_actions()
+            .into_iter()
+            .find(|x| x.name == name)
+            .ok_or(anyhow::anyhow!("action {} not found", name))?;
+
+        let internal_txn = if txn.is_none() {
+            Some(self.conn.get_connection().begin().await?)
+        } else {
+            None
+        };
+        let cur_txn = match txn {
+            Some(txn) => txn,
+            None => internal_txn.as_ref().unwrap(),
+        };
+
+        let message = action.run(cur_txn, rows).await?;
+
+        if let Some(txn_data) = internal_txn {
+            txn_data.commit().await?;
+        }
+        Ok(message)
+    }
+
     pub async fn get_delete_template(
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
         row: &Json,
+        user: &AdminUser,
+        csrf_token: &str,
     ) -> Result<templates::AdminDeleteForm> {
         let id = model.json_to_key(row)?;
+        let related = self
+            .get_related_for_delete(model.as_ref(), row, user)
+            .await?;
+        let mut lines = Vec::new();
+        self.delete_tree_lines(&related, 0, &mut lines);
         Ok(templates::AdminDeleteForm {
-            site: self.site.clone(),
+            site: self.get_site(user),
+            csrf_token: csrf_token.into(),
             form_id: format!("{}-delete", model.get_table_name()),
             page_id: id,
             model_name: model.get_table_name().into(),
             action: None,
             method: "POST".into(),
             fields: self
-                .get_form_fields(
-                    model.get_form_fields(),
-                    Some(row),
-                    model.get_primary_keys(),
-                    FormType::DELETE,
-                )
+                .get_form_fields(model.as_ref(), Some(row), FormType::DELETE)
                 .await?,
+            related: lines,
+            blocked: delete_is_blocked(&related),
+            unchecked: delete_is_unchecked(&related),
         })
     }
+
+    fn delete_tree_lines(
+        &self,
+        groups: &[RelatedRows],
+        depth: usize,
+        lines: &mut Vec<templates::DeleteTreeLine>,
+    ) {
+        for group in groups {
+            lines.push(templates::DeleteTreeLine {
+                depth,
+                label: format!(
+                    "{} {} ({})",
+                    group.count,
+                    group.table_name,
+                    group.columns.join(", ")
+                ),
+                link: group.viewable.then(|| {
+                    format!(
+                        "{}/{}/{}",
+                        self.sub_path(),
+                        group.table_name,
+                        crate::facet::to_link(&HashMap::new(), &group.filter)
+                    )
+                }),
+                effect: Some(group.effect.to_string()),
+                blocks: group.effect == DeleteEffect::Block,
+            });
+            for row in group.rows.iter() {
+                lines.push(templates::DeleteTreeLine {
+                    depth: depth + 1,
+                    label: row.label.clone(),
+                    link: Some(format!(
+                        "{}/{}/view/{}/",
+                        self.sub_path(),
+                        group.table_name,
+                        row.key
+                    )),
+                    effect: None,
+                    blocks: false,
+                });
+                self.delete_tree_lines(&row.related, depth + 2, lines);
+            }
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct AdminBuilder {
     models: Vec<Box<dyn ModelAdminTrait + Send + Sync>>,
+    auth: Option<Box<dyn AdminAuth + Send + Sync>>,
+    audit_log: Option<Box<dyn AuditLog + Send + Sync>>,
 }
 
 impl AdminBuilder {
+    pub fn auth<A>(mut self, auth: A) -> Self
+    where
+        A: AdminAuth + Send + Sync + 'static,
+    {
+        self.auth = Some(Box::new(auth));
+        self
+    }
+
+    pub fn audit_log<L>(mut self, audit_log: L) -> Self
+    where
+        L: AuditLog + Send + Sync + 'static,
+    {
+        self.audit_log = Some(Box::new(audit_log));
+        self
+    }
+
     pub fn add_model<T>(mut self, model_admin: T) -> Self
     where
         T: ModelAdminTrait + Send + Sync + 'static,
@@ -402,6 +1228,7 @@ impl AdminBuilder {
         let mut site = templates::AdminSite {
             title: "Admin".into(),
             models: Vec::new(),
+            addable_models: HashSet::new(),
             sub_path: sub_path.trim_end_matches('/').to_string(),
         };
         let mut tables = HashSet::new();
@@ -419,6 +1246,7 @@ impl AdminBuilder {
             }
 
             site.models.push(table_name.clone());
+            site.addable_models.insert(table_name.clone());
             models.insert(table_name.clone(), model_admin);
         }
 
@@ -430,6 +1258,8 @@ impl AdminBuilder {
 
         Ok(Admin {
             conn: Box::new(ConnectorImpl { conn }),
+            auth: self.auth,
+            audit_log: self.audit_log,
             models,
             site,
         })
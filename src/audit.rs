@@ -0,0 +1,145 @@
+use crate::{Json, Result};
+use async_trait::async_trait;
+use sea_orm::DatabaseTransaction;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+        }
+    }
+}
+
+/// One change made through the admin. `before` is empty for creations, `after` for deletions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub table_name: String,
+    pub object_key: String,
+    pub action: AuditAction,
+    pub actor: String,
+    pub timestamp: SystemTime,
+    pub before: Option<Json>,
+    pub after: Option<Json>,
+}
+
+#[async_trait]
+pub trait AuditLog {
+    /// Called inside the transaction of the change, an error rolls the change back.
+    async fn record(&self, txn: &DatabaseTransaction, entry: &AuditEntry) -> Result<()>;
+}
+
+// ----------------------------------------------------------------------------
+#[cfg(feature = "with-chrono")]
+pub mod admin_log {
+    use sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+    #[sea_orm(table_name = "admin_log")]
+    pub struct Model {
+        #[sea_orm(primary_key)]
+        pub id: i64,
+        pub action_time: DateTimeUtc,
+        pub actor: String,
+        pub table_name: String,
+        pub object_key: String,
+        pub action: String,
+        pub before: Option<Json>,
+        pub after: Option<Json>,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+
+    impl ActiveModelBehavior for ActiveModel {}
+}
+
+/// Stores entries in the `admin_log` table.
+#[cfg(feature = "with-chrono")]
+#[derive(Debug, Default, Clone)]
+pub struct SeaOrmAuditLog;
+
+#[cfg(feature = "with-chrono")]
+impl SeaOrmAuditLog {
+    /// `CREATE TABLE IF NOT EXISTS admin_log`, to run from a migration.
+    pub fn create_table_statement(
+        backend: sea_orm::DbBackend,
+    ) -> sea_orm::sea_query::TableCreateStatement {
+        sea_orm::Schema::new(backend)
+            .create_table_from_entity(admin_log::Entity)
+            .if_not_exists()
+            .to_owned()
+    }
+
+    /// Index looking up the entries of a row.
+    pub fn create_index_statement() -> sea_orm::sea_query::IndexCreateStatement {
+        sea_orm::sea_query::Index::create()
+            .name("admin_log_object")
+            .table(admin_log::Entity)
+            .col(admin_log::Column::TableName)
+            .col(admin_log::Column::ObjectKey)
+            .if_not_exists()
+            .to_owned()
+    }
+
+    /// Creates the `admin_log` table and its index unless they exist.
+    pub async fn create_table(conn: &sea_orm::DatabaseConnection) -> Result<()> {
+        use sea_orm::ConnectionTrait;
+
+        let backend = conn.get_database_backend();
+        conn.execute(backend.build(&Self::create_table_statement(backend)))
+            .await?;
+        conn.execute(backend.build(&Self::create_index_statement()))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "with-chrono")]
+#[async_trait]
+impl AuditLog for SeaOrmAuditLog {
+    async fn record(&self, txn: &DatabaseTransaction, entry: &AuditEntry) -> Result<()> {
+        use sea_orm::{ActiveModelTrait, NotSet, Set};
+
+        admin_log::ActiveModel {
+            id: NotSet,
+            action_time: Set(entry.timestamp.into()),
+            actor: Set(entry.actor.clone()),
+            table_name: Set(entry.table_name.clone()),
+            object_key: Set(entry.object_key.clone()),
+            action: Set(entry.action.as_str().into()),
+            before: Set(entry.before.clone()),
+            after: Set(entry.after.clone()),
+        }
+        .insert(txn)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "with-chrono")]
+fn audit_log_permission(user: &crate::AdminUser, permission: crate::AdminPermission) -> bool {
+    permission == crate::AdminPermission::View
+        && user.has_perm("admin_log", crate::AdminPermission::View)
+}
+
+/// Read-only admin for `admin_log`, register it with `add_model` to browse the log.
+#[cfg(feature = "with-chrono")]
+#[derive(crate::ModelAdmin, Default)]
+#[model_admin(
+    module = admin_log,
+    list_display = [Id, ActionTime, Actor, TableName, ObjectKey, Action],
+    search_fields = [Actor, TableName, ObjectKey],
+    ordering = [(Id, Desc)],
+    permission = audit_log_permission
+)]
+pub struct AuditLogAdmin;
@@ -0,0 +1,315 @@
+use crate::Result;
+use async_trait::async_trait;
+use rand::{distributions::Alphanumeric, Rng};
+use sea_orm::DatabaseConnection;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdminUser {
+    pub username: String,
+    pub is_superuser: bool,
+    pub permissions: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminPermission {
+    View,
+    Add,
+    Change,
+    Delete,
+}
+
+impl AdminPermission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdminPermission::View => "view",
+            AdminPermission::Add => "add",
+            AdminPermission::Change => "change",
+            AdminPermission::Delete => "delete",
+        }
+    }
+}
+
+impl AdminUser {
+    pub fn new(username: &str) -> Self {
+        AdminUser {
+            username: username.into(),
+            is_superuser: false,
+            permissions: HashSet::new(),
+        }
+    }
+
+    pub fn superuser(username: &str) -> Self {
+        AdminUser {
+            username: username.into(),
+            is_superuser: true,
+            permissions: HashSet::new(),
+        }
+    }
+
+    /// Grants `permission` on `table_name`, e.g. `user.with_perm("author", AdminPermission::Change)`.
+    pub fn with_perm(mut self, table_name: &str, permission: AdminPermission) -> Self {
+        self.permissions
+            .insert(format!("{}.{}", table_name, permission.as_str()));
+        self
+    }
+
+    pub fn has_perm(&self, table_name: &str, permission: AdminPermission) -> bool {
+        self.is_superuser
+            || self
+                .permissions
+                .contains(&format!("{}.{}", table_name, permission.as_str()))
+    }
+}
+
+// ----------------------------------------------------------------------------
+/// Credentials extracted from an incoming request, independent of the web framework.
+#[derive(Debug, Clone, Default)]
+pub struct AuthRequest {
+    pub cookies: HashMap<String, String>,
+    pub authorization: Option<String>,
+}
+
+impl AuthRequest {
+    pub fn from_headers(cookie: Option<&str>, authorization: Option<&str>) -> Self {
+        AuthRequest {
+            cookies: cookie.map(parse_cookie_header).unwrap_or_default(),
+            authorization: authorization.map(|x| x.to_string()),
+        }
+    }
+
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.cookies.get(name).map(|x| x.as_str())
+    }
+
+    pub fn bearer_token(&self) -> Option<&str> {
+        let authorization = self.authorization.as_ref()?.trim();
+        let (scheme, token) = authorization.split_once(' ')?;
+        if scheme.eq_ignore_ascii_case("bearer") && !token.trim().is_empty() {
+            Some(token.trim())
+        } else {
+            None
+        }
+    }
+}
+
+pub fn parse_cookie_header(header: &str) -> HashMap<String, String> {
+    header
+        .split(';')
+        .filter_map(|x| x.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().trim_matches('"').to_string()))
+        .filter(|(k, _)| !k.is_empty())
+        .collect()
+}
+
+// ----------------------------------------------------------------------------
+#[async_trait]
+pub trait AdminAuth {
+    /// Resolves the user of the request. `Ok(None)` means the request is not authenticated.
+    async fn authenticate(
+        &self,
+        conn: &DatabaseConnection,
+        request: &AuthRequest,
+    ) -> Result<Option<AdminUser>>;
+
+    /// Where unauthenticated html requests are redirected to.
+    fn login_url(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Looks up the user owning a session id or an api token.
+#[async_trait]
+pub trait UserResolver {
+    async fn resolve(
+        &self,
+        conn: &DatabaseConnection,
+        credential: &str,
+    ) -> Result<Option<AdminUser>>;
+}
+
+#[async_trait]
+impl UserResolver for HashMap<String, AdminUser> {
+    async fn resolve(
+        &self,
+        _conn: &DatabaseConnection,
+        credential: &str,
+    ) -> Result<Option<AdminUser>> {
+        Ok(self.get(credential).cloned())
+    }
+}
+
+#[derive(Default)]
+pub struct MemorySessionStore {
+    sessions: RwLock<HashMap<String, AdminUser>>,
+}
+
+impl MemorySessionStore {
+    /// Stores the user under a new random session id and returns the id.
+    pub fn login(&self, user: AdminUser) -> String {
+        let session_id: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(48)
+            .map(char::from)
+            .collect();
+        self.sessions
+            .write()
+            .unwrap()
+            .insert(session_id.clone(), user);
+        session_id
+    }
+
+    pub fn logout(&self, session_id: &str) {
+        self.sessions.write().unwrap().remove(session_id);
+    }
+}
+
+#[async_trait]
+impl UserResolver for MemorySessionStore {
+    async fn resolve(
+        &self,
+        _conn: &DatabaseConnection,
+        credential: &str,
+    ) -> Result<Option<AdminUser>> {
+        Ok(self.sessions.read().unwrap().get(credential).cloned())
+    }
+}
+
+#[async_trait]
+impl<R> UserResolver for std::sync::Arc<R>
+where
+    R: UserResolver + Send + Sync + ?Sized,
+{
+    async fn resolve(
+        &self,
+        conn: &DatabaseConnection,
+        credential: &str,
+    ) -> Result<Option<AdminUser>> {
+        self.as_ref().resolve(conn, credential).await
+    }
+}
+
+// ----------------------------------------------------------------------------
+pub struct SessionCookieAuth<R> {
+    cookie_name: String,
+    login_url: String,
+    resolver: R,
+}
+
+impl<R> SessionCookieAuth<R>
+where
+    R: UserResolver + Send + Sync,
+{
+    pub fn new(cookie_name: &str, login_url: &str, resolver: R) -> Self {
+        SessionCookieAuth {
+            cookie_name: cookie_name.into(),
+            login_url: login_url.into(),
+            resolver,
+        }
+    }
+}
+
+#[async_trait]
+impl<R> AdminAuth for SessionCookieAuth<R>
+where
+    R: UserResolver + Send + Sync,
+{
+    async fn authenticate(
+        &self,
+        conn: &DatabaseConnection,
+        request: &AuthRequest,
+    ) -> Result<Option<AdminUser>> {
+        match request.cookie(&self.cookie_name) {
+            Some(session_id) if !session_id.is_empty() => {
+                self.resolver.resolve(conn, session_id).await
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn login_url(&self) -> Option<String> {
+        Some(self.login_url.clone())
+    }
+}
+
+pub struct BearerTokenAuth<R> {
+    resolver: R,
+}
+
+impl<R> BearerTokenAuth<R>
+where
+    R: UserResolver + Send + Sync,
+{
+    pub fn new(resolver: R) -> Self {
+        BearerTokenAuth { resolver }
+    }
+}
+
+#[async_trait]
+impl<R> AdminAuth for BearerTokenAuth<R>
+where
+    R: UserResolver + Send + Sync,
+{
+    async fn authenticate(
+        &self,
+        conn: &DatabaseConnection,
+        request: &AuthRequest,
+    ) -> Result<Option<AdminUser>> {
+        match request.bearer_token() {
+            Some(token) => self.resolver.resolve(conn, token).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cookie_header() {
+        let cookies = parse_cookie_header("a=1; session = abc ;b=\"x=y\"; broken");
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(cookies["a"], "1");
+        assert_eq!(cookies["session"], "abc");
+        assert_eq!(cookies["b"], "x=y");
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let request = AuthRequest::from_headers(None, Some("Bearer  token-1"));
+        assert_eq!(request.bearer_token(), Some("token-1"));
+        let request = AuthRequest::from_headers(None, Some("Basic dXNlcjpwYXNz"));
+        assert_eq!(request.bearer_token(), None);
+        let request = AuthRequest::from_headers(None, None);
+        assert_eq!(request.bearer_token(), None);
+    }
+
+    #[test]
+    fn test_has_perm() {
+        let user = AdminUser::new("bob").with_perm("author", AdminPermission::View);
+        assert!(user.has_perm("author", AdminPermission::View));
+        assert!(!user.has_perm("author", AdminPermission::Change));
+        assert!(!user.has_perm("post", AdminPermission::View));
+        assert!(AdminUser::superuser("root").has_perm("post", AdminPermission::Delete));
+    }
+
+    #[tokio::test]
+    async fn test_session_cookie_auth() {
+        let conn = DatabaseConnection::Disconnected;
+        let store = std::sync::Arc::new(MemorySessionStore::default());
+        let auth = SessionCookieAuth::new("admin_session", "/login", store.clone());
+        let session_id = store.login(AdminUser::new("alice"));
+
+        let request =
+            AuthRequest::from_headers(Some(&format!("admin_session={}", session_id)), None);
+        let user = auth.authenticate(&conn, &request).await.unwrap();
+        assert_eq!(user.map(|x| x.username), Some("alice".to_string()));
+
+        store.logout(&session_id);
+        assert_eq!(auth.authenticate(&conn, &request).await.unwrap(), None);
+    }
+}
@@ -1,318 +1,67 @@
-use crate::create_cond_from_json;
-
-use super::{json_overwrite_key, templates, Admin, ModelAdminTrait};
-use askama::Template;
+use super::{Admin, AdminBody, AdminRequest, AdminResponse, CSRF_HEADER_NAME, MAX_BODY_SIZE};
 use axum::{
-    extract::{Extension, Path, Query},
-    http::StatusCode,
+    body::Body,
+    extract::{Extension, OriginalUri, Request},
+    http::{header, HeaderName, HeaderValue, StatusCode},
     response::{Html, IntoResponse, Json, Response},
-    routing::get,
+    routing::any,
     Router,
 };
-use axum_extra::{headers, TypedHeader};
-use log::error;
-use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
-
-// ----- HtmlOrJson -----
-#[derive(Debug, Clone)]
-enum HtmlOrJson {
-    Json(Json<AnyData>),
-    Html(Html<String>),
-}
+use std::sync::Arc;
 
-impl IntoResponse for HtmlOrJson {
+impl IntoResponse for AdminResponse {
     fn into_response(self) -> Response {
-        match self {
-            HtmlOrJson::Json(j) => j.into_response(),
-            HtmlOrJson::Html(h) => h.into_response(),
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = match self.body {
+            AdminBody::Empty => status.into_response(),
+            AdminBody::Html(html) => (status, Html(html)).into_response(),
+            AdminBody::Json(value) => (status, Json(value)).into_response(),
+            AdminBody::Stream(stream) => (status, Body::from_stream(stream)).into_response(),
+        };
+        for (name, value) in self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                response.headers_mut().append(name, value);
+            }
         }
+        response
     }
 }
 
-// ----- RequestInfo -----
-#[derive(Debug, Clone)]
-pub struct RequestInfo {
-    pub accept_html: bool,
-    pub accept_json: bool,
-    pub path: String,
-    pub query: HashMap<String, Vec<String>>,
-}
-
-// ----- AnyData -----
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct AnyData(serde_json::Value);
-
-// ----- Accept -----
-enum RequestHeaderAccept {
-    Json,
-    Html,
-}
-
-impl headers::Header for RequestHeaderAccept {
-    fn name() -> &'static headers::HeaderName {
-        &axum::http::header::ACCEPT
-    }
-
-    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
-    where
-        Self: Sized,
-        I: Iterator<Item = &'i axum::http::HeaderValue>,
-    {
-        let value = values.next().ok_or_else(headers::Error::invalid)?;
-        let value = value.to_str().map_err(|_| headers::Error::invalid())?;
-
-        if value.contains("json") {
-            Ok(RequestHeaderAccept::Json)
-        } else {
-            Ok(RequestHeaderAccept::Html)
-        }
-    }
-
-    fn encode<E: Extend<axum::http::HeaderValue>>(&self, _values: &mut E) {
-        // never
-    }
-}
-
-// ----- return json -----
-fn return_json_object(
-    model: &Box<dyn ModelAdminTrait + Send + Sync>,
-    r: super::Result<super::Json>,
-) -> (StatusCode, Json<AnyData>) {
-    match r {
-        Ok(data) => (
-            StatusCode::OK,
-            Json(AnyData(serde_json::json!({
-                "status": "ok",
-                // TODO: ErrのときInternalServerErrorにしないと、、
-                "key": serde_json::Value::String(model.json_to_key(&data).unwrap()),
-                "label": serde_json::Value::String(model.to_str(&data).unwrap()),
-                "data": data,
-            }))),
-        ),
-        Err(error) => {
-            error!("Error: {error:?}");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(AnyData(serde_json::json!({
-                    "status": "failed",
-                    "error": format!("{}", error)
-                }))),
-            )
-        }
-    }
-}
-fn return_json<T>(r: super::Result<T>) -> (StatusCode, Json<AnyData>) {
-    match r {
-        Ok(_) => (
-            StatusCode::OK,
-            Json(AnyData(serde_json::json!({
-                "status": "ok"
-            }))),
-        ),
-        Err(error) => {
-            error!("Error: {error:?}");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(AnyData(serde_json::json!({
-                    "status": "failed",
-                    "error": format!("{}", error)
-                }))),
-            )
-        }
-    }
-}
-
-// ----- routes -----
-async fn index(Extension(admin): Extension<Arc<Admin>>) -> Result<Html<String>, StatusCode> {
-    let template = templates::AdminIndex::new(&admin.site).map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    Ok(Html(template.render().unwrap()))
-}
-
-async fn list(
-    Path(model): Path<String>,
-    Extension(admin): Extension<Arc<Admin>>,
-    TypedHeader(accept): TypedHeader<RequestHeaderAccept>,
-    Query(query): Query<HashMap<String, String>>, // TODO: array not supported
-) -> Result<HtmlOrJson, StatusCode> {
-    let request_info = RequestInfo {
-        accept_html: true,
-        accept_json: false,
-        path: "/".to_string(),
-        query: query.into_iter().map(|(k, v)| (k, vec![v])).collect(),
+async fn handle(Extension(admin): Extension<Arc<Admin>>, request: Request) -> Response {
+    let (parts, body) = request.into_parts();
+    // nested routers strip the prefix from `uri`, so prefer the original one
+    let uri = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map(|x| x.0.clone())
+        .unwrap_or(parts.uri);
+    let body = match axum::body::to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(body) => body,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
     };
-    let model = admin.models.get(&model).ok_or(StatusCode::NOT_FOUND)?;
-    match accept {
-        RequestHeaderAccept::Json => {
-            let object_list = admin
-                .get_list_as_json(model, &request_info.query)
-                .await
-                .map_err(|error| {
-                    error!("Error: {error:?}");
-                    StatusCode::INTERNAL_SERVER_ERROR
-                })?;
-            Ok(HtmlOrJson::Json(Json(AnyData(object_list))))
-        }
-        RequestHeaderAccept::Html => {
-            let template = admin
-                .get_list_template(model, &request_info.query)
-                .await
-                .map_err(|error| {
-                    error!("Error: {error:?}");
-                    StatusCode::INTERNAL_SERVER_ERROR
-                })?;
 
-            Ok(HtmlOrJson::Html(Html(template.render().unwrap())))
+    let mut request = AdminRequest::new(parts.method.as_str(), uri.path())
+        .query(uri.query())
+        .body(body.to_vec());
+    for name in [
+        header::ACCEPT.as_str(),
+        header::AUTHORIZATION.as_str(),
+        header::CONTENT_TYPE.as_str(),
+        header::COOKIE.as_str(),
+        CSRF_HEADER_NAME,
+    ] {
+        if let Some(value) = parts.headers.get(name).and_then(|x| x.to_str().ok()) {
+            request = request.header(name, value);
         }
     }
-}
-
-async fn get_create_template(
-    Path(model): Path<String>,
-    Extension(admin): Extension<Arc<Admin>>,
-) -> Result<Html<String>, StatusCode> {
-    let model = admin.models.get(&model).ok_or(StatusCode::NOT_FOUND)?;
-    let template = admin.get_create_template(model).await.map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    Ok(Html(template.render().unwrap()))
-}
-
-async fn create_model<'r>(
-    Path(model): Path<String>,
-    Extension(admin): Extension<Arc<Admin>>,
-    Json(data): Json<AnyData>,
-) -> Result<(StatusCode, Json<AnyData>), StatusCode> {
-    let model = admin.models.get(&model).ok_or(StatusCode::NOT_FOUND)?;
-    Ok(return_json_object(
-        model,
-        admin.create(model, &data.0, None).await,
-    ))
-}
-
-async fn get_update_template(
-    Path((model, id)): Path<(String, String)>,
-    Extension(admin): Extension<Arc<Admin>>,
-) -> Result<Html<String>, StatusCode> {
-    let model = admin.models.get(&model).ok_or(StatusCode::NOT_FOUND)?;
-    let key = model.key_to_json(&id).map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::BAD_REQUEST
-    })?;
-    let cond = create_cond_from_json(&model.get_primary_keys(), &key, true).map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::BAD_REQUEST
-    })?;
-    let row = model
-        .get(&admin.get_connection(), &cond)
-        .await
-        .map_err(|error| {
-            error!("Error: {error:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
-
-    let template = admin
-        .get_update_template(model, &row)
-        .await
-        .map_err(|error| {
-            error!("Error: {error:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    Ok(Html(template.render().unwrap()))
-}
-
-async fn update_model(
-    Path((model, id)): Path<(String, String)>,
-    Extension(admin): Extension<Arc<Admin>>,
-    Json(data): Json<AnyData>,
-) -> Result<(StatusCode, Json<AnyData>), StatusCode> {
-    let model = admin.models.get(&model).ok_or(StatusCode::NOT_FOUND)?;
-    let key = model.key_to_json(&id).map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::BAD_REQUEST
-    })?;
-    let data = json_overwrite_key(&data.0, &key).map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    Ok(return_json_object(
-        model,
-        admin.update(model, &data, None).await,
-    ))
-}
-
-async fn get_delete_template(
-    Path((model, id)): Path<(String, String)>,
-    Extension(admin): Extension<Arc<Admin>>,
-) -> Result<Html<String>, StatusCode> {
-    let model = admin.models.get(&model).ok_or(StatusCode::NOT_FOUND)?;
-    let key = model.key_to_json(&id).map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::BAD_REQUEST
-    })?;
-    let cond = create_cond_from_json(&model.get_primary_keys(), &key, true).map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::BAD_REQUEST
-    })?;
-    let row = model
-        .get(&admin.get_connection(), &cond)
-        .await
-        .map_err(|error| {
-            error!("Error: {error:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or(StatusCode::NOT_FOUND)?;
-
-    let template = admin
-        .get_delete_template(model, &row)
-        .await
-        .map_err(|error| {
-            error!("Error: {error:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    Ok(Html(template.render().unwrap()))
-}
-
-async fn delete_model(
-    Path((model, id)): Path<(String, String)>,
-    Extension(admin): Extension<Arc<Admin>>,
-    Json(data): Json<AnyData>,
-) -> Result<(StatusCode, Json<AnyData>), StatusCode> {
-    let model = admin.models.get(&model).ok_or(StatusCode::NOT_FOUND)?;
-    let key = model.key_to_json(&id).map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::BAD_REQUEST
-    })?;
-
-    let data = json_overwrite_key(&data.0, &key).map_err(|error| {
-        error!("Error: {error:?}");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    Ok(return_json(admin.delete(model, &data, None).await))
+    admin.handle(request).await.into_response()
 }
 
 pub fn get_router() -> Router {
     Router::new()
-        .route("/", get(index))
-        .route("/:model/", get(list))
-        .route(
-            "/:model/create/",
-            get(get_create_template).post(create_model),
-        )
-        .route(
-            "/:model/update/:id/",
-            get(get_update_template).post(update_model),
-        )
-        .route(
-            "/:model/delete/:id/",
-            get(get_delete_template).post(delete_model),
-        )
+        .route("/", any(handle))
+        .route("/*path", any(handle))
 }
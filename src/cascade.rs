@@ -0,0 +1,311 @@
+use crate::{
+    create_cond_from_json, extract_table_name, identity_to_vec_string, json_force_str, Admin,
+    AdminUser, Json, ListParam, ModelAdminTrait, Result,
+};
+use futures_util::future::{BoxFuture, FutureExt};
+use sea_orm::sea_query::{Alias, Expr, ForeignKeyAction, Query, SimpleExpr};
+use sea_orm::{Condition, RelationDef};
+use std::collections::HashSet;
+
+/// Rows listed for each group of related rows, `RelatedRows::count` covers all of them.
+const RELATED_ROWS_SHOWN: u64 = 20;
+/// Levels of cascaded rows walked below the deleted row.
+const MAX_CASCADE_DEPTH: usize = 5;
+/// Listed rows whose own related rows are listed, over the whole tree.
+const MAX_ROWS_EXPANDED: usize = 100;
+
+/// What deleting a row does to the rows referencing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteEffect {
+    Cascade,
+    SetNull,
+    SetDefault,
+    /// `Restrict` and `NoAction`, also used without `on_delete` since the database default rejects the delete.
+    Block,
+}
+
+impl DeleteEffect {
+    pub fn from_action(action: Option<&ForeignKeyAction>) -> Self {
+        match action {
+            Some(ForeignKeyAction::Cascade) => DeleteEffect::Cascade,
+            Some(ForeignKeyAction::SetNull) => DeleteEffect::SetNull,
+            Some(ForeignKeyAction::SetDefault) => DeleteEffect::SetDefault,
+            _ => DeleteEffect::Block,
+        }
+    }
+}
+
+impl std::fmt::Display for DeleteEffect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DeleteEffect::Cascade => "deleted",
+            DeleteEffect::SetNull => "set to null",
+            DeleteEffect::SetDefault => "set to default",
+            DeleteEffect::Block => "blocks the delete",
+        })
+    }
+}
+
+/// Rows of `table_name` referencing a row through `columns`.
+#[derive(Debug, Clone)]
+pub struct RelatedRows {
+    pub table_name: String,
+    pub columns: Vec<String>,
+    pub effect: DeleteEffect,
+    pub count: u64,
+    /// List filters selecting the rows of the group.
+    pub filter: Vec<(String, String)>,
+    /// The first rows of the group, empty when the user can not view `table_name`.
+    pub rows: Vec<RelatedRow>,
+    pub viewable: bool,
+    /// A blocking row is in the group or below one of its cascaded rows, listed or not.
+    pub blocked: bool,
+    /// Rows are cascaded deeper than `MAX_CASCADE_DEPTH`, they were not checked for blocking rows.
+    pub unchecked: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RelatedRow {
+    pub key: String,
+    pub label: String,
+    /// Rows referencing this one, only walked for cascaded rows.
+    pub related: Vec<RelatedRows>,
+}
+
+pub fn delete_is_blocked(related: &[RelatedRows]) -> bool {
+    related.iter().any(|x| x.blocked)
+}
+
+/// Whether the cascade went too deep to tell if the delete is blocked.
+pub fn delete_is_unchecked(related: &[RelatedRows]) -> bool {
+    related.iter().any(|x| x.unchecked)
+}
+
+impl Admin {
+    /// Rows of the registered models referencing `row` through their `belongs_to` relations,
+    /// with the rows cascaded from them. Rows of models `user` can not view are only counted.
+    pub async fn get_related_for_delete(
+        &self,
+        model: &(dyn ModelAdminTrait + Send + Sync),
+        row: &Json,
+        user: &AdminUser,
+    ) -> Result<Vec<RelatedRows>> {
+        let mut walk = Walk {
+            visited: HashSet::new(),
+            expanded: 0,
+        };
+        walk.visited
+            .insert((model.get_table_name().to_string(), model.json_to_key(row)?));
+        collect_related(
+            self,
+            user,
+            model.get_table_name().to_string(),
+            row.clone(),
+            0,
+            &mut walk,
+        )
+        .await
+    }
+}
+
+struct Walk {
+    visited: HashSet<(String, String)>,
+    expanded: usize,
+}
+
+/// `belongs_to` relations of the registered models to `table_name`, sorted by model.
+fn referencing_relations<'a>(
+    admin: &'a Admin,
+    table_name: &str,
+) -> Result<
+    Vec<(
+        &'a String,
+        &'a (dyn ModelAdminTrait + Send + Sync),
+        RelationDef,
+    )>,
+> {
+    let mut table_names: Vec<&String> = admin.models.keys().collect();
+    table_names.sort();
+    let mut relations = Vec::new();
+    for related_name in table_names {
+        let related_model = admin.models[related_name].as_ref();
+        for def in related_model.get_relations() {
+            if !def.is_owner
+                && extract_table_name(&def.from_tbl)? == *related_name
+                && extract_table_name(&def.to_tbl)? == table_name
+            {
+                relations.push((related_name, related_model, def));
+            }
+        }
+    }
+    Ok(relations)
+}
+
+/// Rows referencing any row of `table_name` matching `cond`, i.e. `(from_col) IN (SELECT to_col ..)`.
+fn referencing_cond(def: &RelationDef, table_name: &str, cond: Condition) -> Condition {
+    let from: Vec<SimpleExpr> = identity_to_vec_string(&def.from_col)
+        .into_iter()
+        .map(|x| Expr::col(Alias::new(x)).into())
+        .collect();
+    let mut select = Query::select();
+    for to in identity_to_vec_string(&def.to_col) {
+        select.column(Alias::new(to));
+    }
+    select.from(Alias::new(table_name)).cond_where(cond);
+    let expr = match <[SimpleExpr; 1]>::try_from(from) {
+        Ok([col]) => Expr::expr(col).in_subquery(select.to_owned()),
+        Err(cols) => Expr::tuple(cols).in_subquery(select.to_owned()),
+    };
+    Condition::all().add(expr)
+}
+
+/// `(blocked, unchecked)` of every row cascaded from the rows of `table_name` matching `cond`,
+/// with one query per relation and level instead of one per row.
+fn set_is_blocked<'a>(
+    admin: &'a Admin,
+    table_name: String,
+    cond: Condition,
+    depth: usize,
+) -> BoxFuture<'a, Result<(bool, bool)>> {
+    async move {
+        let mut unchecked = false;
+        for (related_name, related_model, def) in referencing_relations(admin, &table_name)? {
+            let effect = DeleteEffect::from_action(def.on_delete.as_ref());
+            if !matches!(effect, DeleteEffect::Block | DeleteEffect::Cascade) {
+                continue;
+            }
+            let related_cond = referencing_cond(&def, &table_name, cond.clone());
+            if related_model
+                .count(admin.get_connection(), &related_cond)
+                .await?
+                == 0
+            {
+                continue;
+            }
+            if effect == DeleteEffect::Block {
+                return Ok((true, false));
+            }
+            if depth + 1 >= MAX_CASCADE_DEPTH {
+                unchecked = true;
+                continue;
+            }
+            let (blocked, below) =
+                set_is_blocked(admin, related_name.clone(), related_cond, depth + 1).await?;
+            if blocked {
+                return Ok((true, false));
+            }
+            unchecked |= below;
+        }
+        Ok((false, unchecked))
+    }
+    .boxed()
+}
+
+fn collect_related<'a>(
+    admin: &'a Admin,
+    user: &'a AdminUser,
+    table_name: String,
+    row: Json,
+    depth: usize,
+    walk: &'a mut Walk,
+) -> BoxFuture<'a, Result<Vec<RelatedRows>>> {
+    async move {
+        let mut groups = Vec::new();
+        for (related_name, related_model, def) in referencing_relations(admin, &table_name)? {
+            let columns = identity_to_vec_string(&def.from_col);
+            let mut key = serde_json::Map::new();
+            for (from, to) in columns.iter().zip(identity_to_vec_string(&def.to_col)) {
+                match row.get(&to) {
+                    Some(value) if !value.is_null() => {
+                        key.insert(from.clone(), value.clone());
+                    }
+                    _ => {}
+                }
+            }
+            if key.len() != columns.len() {
+                continue;
+            }
+            let filter = key
+                .iter()
+                .map(|(column, value)| (column.clone(), json_force_str(value)))
+                .collect();
+            let cond = create_cond_from_json(
+                &related_model
+                    .get_columns()
+                    .into_iter()
+                    .map(|x| x.0)
+                    .collect(),
+                &Json::Object(key),
+                false,
+            )?;
+            let count = related_model.count(admin.get_connection(), &cond).await?;
+            if count == 0 {
+                continue;
+            }
+
+            let effect = DeleteEffect::from_action(def.on_delete.as_ref());
+            let (blocked, unchecked) = match effect {
+                DeleteEffect::Block => (true, false),
+                DeleteEffect::Cascade => {
+                    set_is_blocked(admin, related_name.clone(), cond.clone(), depth + 1).await?
+                }
+                _ => (false, false),
+            };
+            let viewable = related_model.has_view_permission(user);
+            let mut shown = Vec::new();
+            if viewable {
+                let (_, rows) = related_model
+                    .list(
+                        admin.get_connection(),
+                        &ListParam {
+                            cond,
+                            ordering: vec![],
+                            offset: None,
+                            limit: Some(RELATED_ROWS_SHOWN),
+                            count: false,
+                            rank: None,
+                        },
+                    )
+                    .await?;
+                for related_row in rows {
+                    let key = related_model.json_to_key(&related_row)?;
+                    let mut related = Vec::new();
+                    if effect == DeleteEffect::Cascade
+                        && depth + 1 < MAX_CASCADE_DEPTH
+                        && walk.expanded < MAX_ROWS_EXPANDED
+                        && walk.visited.insert((related_name.clone(), key.clone()))
+                    {
+                        walk.expanded += 1;
+                        related = collect_related(
+                            admin,
+                            user,
+                            related_name.clone(),
+                            related_row.clone(),
+                            depth + 1,
+                            walk,
+                        )
+                        .await?;
+                    }
+                    shown.push(RelatedRow {
+                        key,
+                        label: related_model.to_str(&related_row)?,
+                        related,
+                    });
+                }
+            }
+            groups.push(RelatedRows {
+                table_name: related_name.clone(),
+                columns,
+                effect,
+                count,
+                filter,
+                rows: shown,
+                viewable,
+                blocked,
+                unchecked,
+            });
+        }
+        Ok(groups)
+    }
+    .boxed()
+}
@@ -0,0 +1,103 @@
+use crate::{Json, Result};
+use sea_orm::{
+    Condition, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, QueryFilter,
+    QueryTrait, Statement,
+};
+
+/// How the number of rows of a list is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountStrategy {
+    /// `SELECT COUNT(*)` of the filtered rows.
+    Exact,
+    /// The planner estimate of Postgres, `pg_class.reltuples` for unfiltered lists and `EXPLAIN` otherwise.
+    /// Other databases, and tables without statistics, are counted exactly.
+    Estimated,
+    /// No count, the pagination only knows whether there is a next page.
+    None,
+}
+
+/// Number of rows of a list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListTotal {
+    Exact(u64),
+    Estimated(u64),
+    Unknown,
+}
+
+impl ListTotal {
+    pub fn value(&self) -> Option<u64> {
+        match self {
+            ListTotal::Exact(x) | ListTotal::Estimated(x) => Some(*x),
+            ListTotal::Unknown => None,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        matches!(self, ListTotal::Exact(_))
+    }
+}
+
+impl std::fmt::Display for ListTotal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListTotal::Exact(x) => write!(f, "{}", x),
+            ListTotal::Estimated(x) => write!(f, "about {}", x),
+            ListTotal::Unknown => write!(f, "?"),
+        }
+    }
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Planner estimate of the rows of `E` matching `cond`, `None` when there is none.
+pub async fn estimate_rows<E>(conn: &DatabaseConnection, cond: &Condition) -> Result<Option<u64>>
+where
+    E: EntityTrait,
+{
+    if conn.get_database_backend() != DbBackend::Postgres {
+        return Ok(None);
+    }
+
+    if cond.is_empty() {
+        let entity = E::default();
+        let table = match entity.schema_name() {
+            Some(schema) => format!(
+                "{}.{}",
+                quote_ident(schema),
+                quote_ident(entity.table_name())
+            ),
+            None => quote_ident(entity.table_name()),
+        };
+        let row = conn
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT reltuples::bigint AS num_items FROM pg_class WHERE oid = to_regclass($1)",
+                [table.into()],
+            ))
+            .await?;
+        // reltuples is -1 until the table is vacuumed or analyzed
+        return Ok(match row {
+            Some(row) => u64::try_from(row.try_get::<i64>("", "num_items")?).ok(),
+            None => None,
+        });
+    }
+
+    let select = E::find().filter(cond.clone()).build(DbBackend::Postgres);
+    let row = conn
+        .query_one(Statement {
+            sql: format!("EXPLAIN (FORMAT JSON) {}", select.sql),
+            ..select
+        })
+        .await?;
+    Ok(match row {
+        Some(row) => {
+            let plan: Json = row.try_get("", "QUERY PLAN")?;
+            plan[0]["Plan"]["Plan Rows"]
+                .as_f64()
+                .map(|x| x.round() as u64)
+        }
+        None => None,
+    })
+}
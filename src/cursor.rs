@@ -0,0 +1,264 @@
+use crate::{coerce_filter_value, json_force_str, AdminError, Json, ModelAdminTrait, Result};
+use base64::Engine;
+use sea_orm::{
+    sea_query::{Alias, Expr},
+    ColumnDef, Condition, DbBackend, Order,
+};
+use serde::{Deserialize, Serialize};
+
+/// How the list of a model is split into pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pagination {
+    /// Numbered pages from `_p`, i.e. `OFFSET`.
+    Offset,
+    /// Previous / next pages from the opaque `_c` cursor, which seeks from the boundary row
+    /// instead of skipping rows, so deep pages are as fast as the first one.
+    Cursor,
+}
+
+/// Position in a list, the ordering column values of the row next to the page.
+/// `backward` cursors point to the rows before that row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub values: serde_json::Map<String, Json>,
+    pub backward: bool,
+}
+
+impl Cursor {
+    pub fn from_row(row: &Json, ordering: &[(String, Order)], backward: bool) -> Self {
+        Cursor {
+            values: ordering
+                .iter()
+                .map(|(name, _)| (name.clone(), row.get(name).cloned().unwrap_or(Json::Null)))
+                .collect(),
+            backward,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(value: &str) -> Result<Self> {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(value)
+            .ok()
+            .and_then(|x| serde_json::from_slice(&x).ok())
+            .ok_or_else(|| AdminError::field("_c", "invalid cursor").into())
+    }
+}
+
+/// The ordering followed by the primary keys, so that every row has a distinct position.
+pub fn cursor_ordering(
+    ordering: &[(String, Order)],
+    primary_keys: &[String],
+) -> Vec<(String, Order)> {
+    let mut ordering = ordering.to_vec();
+    for key in primary_keys {
+        if !ordering.iter().any(|(name, _)| name == key) {
+            ordering.push((key.clone(), Order::Asc));
+        }
+    }
+    ordering
+}
+
+/// The list ordering of `model`, its `default_ordering` when none of the columns of `ordering` exist,
+/// completed by `cursor_ordering`.
+pub(crate) fn model_cursor_ordering(
+    model: &(dyn ModelAdminTrait + Send + Sync),
+    ordering: &[(String, Order)],
+) -> Vec<(String, Order)> {
+    let columns = model.get_columns();
+    let ordering: Vec<_> = ordering
+        .iter()
+        .filter(|(name, _)| columns.iter().any(|(x, _)| x == name))
+        .cloned()
+        .collect();
+    let ordering = if ordering.is_empty() {
+        model.default_ordering()
+    } else {
+        ordering
+    };
+    cursor_ordering(&ordering, &model.get_primary_keys())
+}
+
+/// The ordering read in the other direction, used to fetch the page before a cursor.
+pub fn reverse_ordering(ordering: &[(String, Order)]) -> Vec<(String, Order)> {
+    ordering
+        .iter()
+        .map(|(name, order)| {
+            let order = match order {
+                Order::Desc => Order::Asc,
+                _ => Order::Desc,
+            };
+            (name.clone(), order)
+        })
+        .collect()
+}
+
+/// Whether NULL sorts after every value, as in Postgres. MySQL and SQLite sort it first.
+fn nulls_are_largest(backend: DbBackend) -> bool {
+    backend == DbBackend::Postgres
+}
+
+/// Rows after (or before, for backward cursors) the cursor, i.e. `a > x OR (a = x AND b > y) ..`.
+/// The row comparison is expanded since the columns may be ordered in different directions.
+/// NULL values of nullable columns are placed where `backend` sorts them.
+pub fn seek_condition(
+    ordering: &[(String, Order)],
+    columns: &[(String, ColumnDef)],
+    cursor: &Cursor,
+    backend: DbBackend,
+) -> Result<Condition> {
+    let invalid = || anyhow::Error::from(AdminError::field("_c", "invalid cursor"));
+    let mut values = Vec::new();
+    for (name, order) in ordering {
+        let (_, def) = columns
+            .iter()
+            .find(|(x, _)| x == name)
+            .ok_or_else(|| anyhow::anyhow!("column {} not found", name))?;
+        let value = match cursor.values.get(name) {
+            None => return Err(invalid()),
+            Some(Json::Null) if def.is_null() => None,
+            Some(Json::Null) => return Err(invalid()),
+            Some(value) => Some(
+                coerce_filter_value(name, def, &json_force_str(value)).map_err(|_| invalid())?,
+            ),
+        };
+        values.push((name, order, def.is_null(), value));
+    }
+
+    let mut cond = Condition::any();
+    for i in 0..values.len() {
+        let mut term = Condition::all();
+        for (name, _, _, value) in &values[..i] {
+            let col = Expr::col(Alias::new(*name));
+            term = term.add(match value {
+                Some(value) => col.eq(value.clone()),
+                None => col.is_null(),
+            });
+        }
+        let (name, order, nullable, value) = &values[i];
+        let col = Expr::col(Alias::new(*name));
+        let greater = matches!(
+            (order, cursor.backward),
+            (Order::Desc, true) | (Order::Asc, false)
+        );
+        // NULL is past the values when seeking towards the side it is sorted on
+        let nulls_after = *nullable && greater == nulls_are_largest(backend);
+        let after = match value {
+            Some(value) => {
+                let after = if greater {
+                    col.gt(value.clone())
+                } else {
+                    col.lt(value.clone())
+                };
+                if nulls_after {
+                    Condition::any()
+                        .add(after)
+                        .add(Expr::col(Alias::new(*name)).is_null())
+                } else {
+                    Condition::all().add(after)
+                }
+            }
+            // every value is past a NULL unless NULL is sorted on the seeked side
+            None if greater != nulls_are_largest(backend) => {
+                Condition::all().add(col.is_not_null())
+            }
+            None => continue,
+        };
+        cond = cond.add(term.add(after));
+    }
+    Ok(cond)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{sea_query::PostgresQueryBuilder, ColumnType, ColumnTypeTrait};
+    use serde_json::json;
+
+    #[test]
+    fn test_seek_condition() {
+        let ordering = cursor_ordering(&[("price".into(), Order::Desc)], &["id".into()]);
+        assert_eq!(
+            ordering,
+            vec![("price".into(), Order::Desc), ("id".into(), Order::Asc)]
+        );
+        let columns = vec![
+            ("id".to_string(), ColumnType::Integer.def()),
+            ("price".to_string(), ColumnType::Integer.def()),
+        ];
+        let cursor = Cursor::from_row(
+            &json!({"id": 3, "name": "x", "price": 300}),
+            &ordering,
+            false,
+        );
+        let cursor = Cursor::decode(&cursor.encode()).unwrap();
+
+        let sql = |cond| {
+            sea_orm::sea_query::Query::select()
+                .column(Alias::new("id"))
+                .from(Alias::new("cake"))
+                .cond_where(cond)
+                .to_string(PostgresQueryBuilder)
+        };
+        assert_eq!(
+            sql(seek_condition(&ordering, &columns, &cursor, DbBackend::Postgres).unwrap()),
+            r#"SELECT "id" FROM "cake" WHERE "price" < 300 OR ("price" = 300 AND "id" > 3)"#
+        );
+        let cursor = Cursor {
+            backward: true,
+            ..cursor
+        };
+        assert_eq!(
+            sql(seek_condition(&ordering, &columns, &cursor, DbBackend::Postgres).unwrap()),
+            r#"SELECT "id" FROM "cake" WHERE "price" > 300 OR ("price" = 300 AND "id" < 3)"#
+        );
+
+        assert!(Cursor::decode("not a cursor").is_err());
+        let cursor = Cursor::from_row(&json!({"id": 3}), &ordering, false);
+        assert!(seek_condition(&ordering, &columns, &cursor, DbBackend::Postgres).is_err());
+    }
+
+    #[test]
+    fn test_seek_condition_nulls() {
+        let ordering = cursor_ordering(&[("price".into(), Order::Asc)], &["id".into()]);
+        let columns = vec![
+            ("id".to_string(), ColumnType::Integer.def()),
+            ("price".to_string(), ColumnType::Integer.def().null()),
+        ];
+        let sql = |row, backward, backend| {
+            let cursor = Cursor::from_row(&row, &ordering, backward);
+            sea_orm::sea_query::Query::select()
+                .column(Alias::new("id"))
+                .from(Alias::new("cake"))
+                .cond_where(seek_condition(&ordering, &columns, &cursor, backend).unwrap())
+                .to_string(PostgresQueryBuilder)
+        };
+
+        // Postgres sorts NULL last in ascending order
+        assert_eq!(
+            sql(json!({"id": 3, "price": 300}), false, DbBackend::Postgres),
+            r#"SELECT "id" FROM "cake" WHERE "price" > 300 OR "price" IS NULL OR ("price" = 300 AND "id" > 3)"#
+        );
+        assert_eq!(
+            sql(json!({"id": 3, "price": null}), false, DbBackend::Postgres),
+            r#"SELECT "id" FROM "cake" WHERE "price" IS NULL AND "id" > 3"#
+        );
+        assert_eq!(
+            sql(json!({"id": 3, "price": null}), true, DbBackend::Postgres),
+            r#"SELECT "id" FROM "cake" WHERE "price" IS NOT NULL OR ("price" IS NULL AND "id" < 3)"#
+        );
+        // SQLite and MySQL first
+        assert_eq!(
+            sql(json!({"id": 3, "price": null}), false, DbBackend::Sqlite),
+            r#"SELECT "id" FROM "cake" WHERE "price" IS NOT NULL OR ("price" IS NULL AND "id" > 3)"#
+        );
+        assert_eq!(
+            sql(json!({"id": 3, "price": 300}), true, DbBackend::Sqlite),
+            r#"SELECT "id" FROM "cake" WHERE "price" < 300 OR "price" IS NULL OR ("price" = 300 AND "id" < 3)"#
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use sea_orm::{DbErr, SqlErr};
+use std::collections::BTreeMap;
+
 #[derive(Debug)]
 pub struct CustomError {
     message: String,
@@ -18,3 +21,135 @@ impl std::fmt::Display for CustomError {
 }
 
 impl std::error::Error for CustomError {}
+
+/// Errors with a meaning for the client, the handlers map them to http statuses with `status`.
+/// Other errors wrapped in `anyhow::Error` are classified by `AdminError::classify`.
+#[derive(Debug)]
+pub enum AdminError {
+    /// 404, the model or row does not exist or the key cannot be decoded.
+    NotFound(String),
+    /// 400, messages keyed by the field (column) they belong to.
+    Validation(BTreeMap<String, Vec<String>>),
+    /// 403
+    PermissionDenied,
+    /// 409, e.g. a unique or foreign key constraint was violated.
+    Conflict(String),
+    /// 500
+    Database(DbErr),
+}
+
+impl AdminError {
+    /// A validation error of a single field.
+    pub fn field(name: impl Into<String>, message: impl Into<String>) -> Self {
+        AdminError::Validation(BTreeMap::from([(name.into(), vec![message.into()])]))
+    }
+
+    pub fn status(&self) -> u16 {
+        match self {
+            AdminError::NotFound(_) => 404,
+            AdminError::Validation(_) => 400,
+            AdminError::PermissionDenied => 403,
+            AdminError::Conflict(_) => 409,
+            AdminError::Database(_) => 500,
+        }
+    }
+
+    /// Messages per field, empty unless this is a `Validation` error.
+    pub fn field_errors(&self) -> BTreeMap<String, Vec<String>> {
+        match self {
+            AdminError::Validation(fields) => fields.clone(),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Status, message and field errors of `error` if it is an `AdminError` or a known database error.
+    /// Database errors are looked up in the chain, constraint violations become 409.
+    pub fn classify(error: &anyhow::Error) -> Option<(u16, String, BTreeMap<String, Vec<String>>)> {
+        let converted;
+        let admin_error = match error.downcast_ref::<AdminError>() {
+            Some(AdminError::Database(e)) => {
+                converted = Self::from_db_err(e);
+                converted.as_ref()
+            }
+            Some(e) => Some(e),
+            None => {
+                converted = error
+                    .chain()
+                    .find_map(|x| x.downcast_ref::<DbErr>())
+                    .and_then(Self::from_db_err);
+                converted.as_ref()
+            }
+        };
+        admin_error.map(|e| (e.status(), e.to_string(), e.field_errors()))
+    }
+
+    fn from_db_err(error: &DbErr) -> Option<AdminError> {
+        match error {
+            DbErr::RecordNotFound(x) => Some(AdminError::NotFound(x.clone())),
+            DbErr::RecordNotUpdated => Some(AdminError::NotFound("record not updated".into())),
+            _ => match error.sql_err() {
+                Some(SqlErr::UniqueConstraintViolation(x))
+                | Some(SqlErr::ForeignKeyConstraintViolation(x)) => Some(AdminError::Conflict(x)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for AdminError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdminError::NotFound(x) => write!(f, "not found: {}", x),
+            AdminError::Validation(fields) => {
+                let messages: Vec<String> = fields
+                    .iter()
+                    .flat_map(|(name, messages)| {
+                        messages.iter().map(move |x| format!("{}: {}", name, x))
+                    })
+                    .collect();
+                write!(f, "invalid input: {}", messages.join(", "))
+            }
+            AdminError::PermissionDenied => write!(f, "permission denied"),
+            AdminError::Conflict(x) => write!(f, "conflict: {}", x),
+            AdminError::Database(x) => write!(f, "database error: {}", x),
+        }
+    }
+}
+
+impl std::error::Error for AdminError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AdminError::Database(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl From<DbErr> for AdminError {
+    fn from(error: DbErr) -> Self {
+        AdminError::from_db_err(&error).unwrap_or(AdminError::Database(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let error: anyhow::Error = AdminError::field("price", "invalid digit").into();
+        let (status, message, fields) = AdminError::classify(&error).unwrap();
+        assert_eq!(status, 400);
+        assert_eq!(message, "invalid input: price: invalid digit");
+        assert_eq!(fields["price"], vec!["invalid digit".to_string()]);
+
+        let error: anyhow::Error = DbErr::RecordNotFound("cake".into()).into();
+        assert_eq!(AdminError::classify(&error).unwrap().0, 404);
+        let error = anyhow::Error::from(DbErr::RecordNotUpdated).context("update");
+        assert_eq!(AdminError::classify(&error).unwrap().0, 404);
+        let error: anyhow::Error = AdminError::from(DbErr::Custom("down".into())).into();
+        assert_eq!(AdminError::classify(&error), None);
+
+        assert_eq!(AdminError::classify(&anyhow::anyhow!("other")), None);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{
+    json_force_str, list_query_to_list_param, model_cursor_ordering, parse_query, seek_condition,
+    Admin, Cursor, Json, Result,
+};
+use futures_util::{stream, Stream};
+use sea_orm::ConnectionTrait;
+use std::{collections::HashMap, sync::Arc};
+
+/// Rows fetched per query while exporting.
+const EXPORT_BATCH_SIZE: u64 = 500;
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn csv_line<I, S>(values: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut line = values
+        .into_iter()
+        .map(|x| csv_field(x.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+fn csv_value(value: Option<&Json>) -> String {
+    value.map(json_force_str).unwrap_or_default()
+}
+
+/// Every row matching the filters and search of `query_param` as CSV lines, header first.
+/// Pagination is ignored and rows are fetched in batches seeked from the last row of the previous one,
+/// ordered as the list with the primary keys breaking ties, so the table is never held in memory.
+pub fn export_csv(
+    admin: Arc<Admin>,
+    model_name: &str,
+    query_param: &HashMap<String, Vec<String>>,
+) -> Result<impl Stream<Item = Result<String>> + Send + 'static> {
+    let model = admin
+        .get_model(model_name)
+        .ok_or(anyhow::anyhow!("model {} not found", model_name))?;
+    let query = parse_query(query_param, model.get_list_per_page())?;
+    let mut param = list_query_to_list_param(
+        &query,
+        model.as_ref(),
+        admin.get_connection().get_database_backend(),
+    )?;
+    let ordering = model_cursor_ordering(model.as_ref(), &query.ordering);
+    param.ordering = ordering.clone();
+    param.offset = None;
+    param.limit = Some(EXPORT_BATCH_SIZE);
+    // rows are positioned by their column values, relevance can not be seeked from
+    param.rank = None;
+    let keys = model.list_display();
+    let header = csv_line(&keys);
+    let model_name = model_name.to_string();
+
+    let rows = stream::try_unfold(Some(None), move |cursor: Option<Option<Cursor>>| {
+        let admin = admin.clone();
+        let model_name = model_name.clone();
+        let keys = keys.clone();
+        let ordering = ordering.clone();
+        let mut param = param.clone();
+        async move {
+            let cursor = match cursor {
+                Some(cursor) => cursor,
+                None => return Ok(None),
+            };
+            let model = admin
+                .get_model(&model_name)
+                .ok_or(anyhow::anyhow!("model {} not found", model_name))?;
+            if let Some(cursor) = &cursor {
+                param.cond = param.cond.add(seek_condition(
+                    &ordering,
+                    &model.get_columns(),
+                    cursor,
+                    admin.get_connection().get_database_backend(),
+                )?);
+            }
+            let (_, rows) = model.list(admin.get_connection(), &param).await?;
+            let last = match rows.last() {
+                Some(last) => last,
+                None => return Ok(None),
+            };
+            let next = if (rows.len() as u64) < EXPORT_BATCH_SIZE {
+                None
+            } else {
+                Some(Some(Cursor::from_row(last, &ordering, false)))
+            };
+            let chunk: String = rows
+                .iter()
+                .map(|row| csv_line(keys.iter().map(|key| csv_value(row.get(key)))))
+                .collect();
+            Ok(Some((chunk, next)))
+        }
+    });
+    Ok(stream::StreamExt::chain(
+        stream::once(async move { Ok(header) }),
+        rows,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_line() {
+        assert_eq!(csv_line(["id", "name"]), "id,name\r\n");
+        assert_eq!(
+            csv_line(["1", "a,b", "say \"hi\"", "two\nlines"]),
+            "1,\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\"\r\n"
+        );
+        assert_eq!(csv_value(Some(&Json::Null)), "");
+        assert_eq!(csv_value(Some(&serde_json::json!(3))), "3");
+    }
+}
@@ -0,0 +1,309 @@
+use crate::{
+    coerce_filter_value, create_cond_from_hash_map, json_force_str, list_query_to_list_param,
+    parse_query, split_lookup,
+    templates::{AdminListFilter, AdminListFilterOption},
+    Admin, AdminField, AdminUser, Json, ListParam, ModelAdminTrait, Result,
+};
+use sea_orm::sea_query::{Alias, Expr};
+use sea_orm::{ColumnDef, ColumnType, Condition, ConnectionTrait};
+use std::collections::HashMap;
+
+/// Values listed by a facet, the most frequent ones.
+const FACET_CHOICES_LIMIT: u64 = 50;
+
+struct FacetChoice {
+    label: String,
+    params: Vec<(String, String)>,
+    count: Option<u64>,
+}
+
+pub(crate) fn to_link(params: &HashMap<String, Vec<String>>, extra: &[(String, String)]) -> String {
+    let mut keys: Vec<_> = params.keys().collect();
+    keys.sort();
+    let mut serializer = form_urlencoded::Serializer::new(String::new());
+    for key in keys {
+        for value in params[key].iter() {
+            serializer.append_pair(key, value);
+        }
+    }
+    for (key, value) in extra {
+        serializer.append_pair(key, value);
+    }
+    format!("?{}", serializer.finish())
+}
+
+fn is_date_column(col_def: &ColumnDef) -> bool {
+    matches!(
+        col_def.get_column_type(),
+        ColumnType::Date
+            | ColumnType::DateTime
+            | ColumnType::Timestamp
+            | ColumnType::TimestampWithTimeZone
+    )
+}
+
+/// today / past 7 days / this month / this year, as `__gte` and `__lt` filters.
+#[cfg(feature = "with-chrono")]
+fn date_ranges(name: &str) -> Vec<(String, Vec<(String, String)>)> {
+    use chrono::{Datelike, Days, Months, NaiveDate};
+
+    let today = chrono::Utc::now().date_naive();
+    let tomorrow = today + Days::new(1);
+    let month = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap();
+    let year = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap();
+    let range = |from: NaiveDate, to: NaiveDate| {
+        vec![
+            (
+                format!("{}__gte", name),
+                from.format("%Y-%m-%d").to_string(),
+            ),
+            (format!("{}__lt", name), to.format("%Y-%m-%d").to_string()),
+        ]
+    };
+    vec![
+        ("today".into(), range(today, tomorrow)),
+        ("past 7 days".into(), range(today - Days::new(7), tomorrow)),
+        ("this month".into(), range(month, month + Months::new(1))),
+        ("this year".into(), range(year, year + Months::new(12))),
+    ]
+}
+
+#[cfg(not(feature = "with-chrono"))]
+fn date_ranges(_name: &str) -> Vec<(String, Vec<(String, String)>)> {
+    Vec::new()
+}
+
+/// Labels of the `table` rows whose `column` is one of `values`, keyed by the value text.
+/// Empty when `table` is not registered or `user` can not view it, the facet shows the values then.
+/// `values` holds at most `FACET_CHOICES_LIMIT` values, they are read with one `IN` query.
+async fn foreign_key_labels(
+    admin: &Admin,
+    user: &AdminUser,
+    table: &str,
+    column: &str,
+    values: &[Json],
+) -> Result<HashMap<String, String>> {
+    let model = match admin.get_model(table) {
+        Some(model) if model.has_view_permission(user) => model,
+        _ => return Ok(HashMap::new()),
+    };
+    if values.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let col_def = model
+        .get_columns()
+        .into_iter()
+        .find(|x| x.0 == column)
+        .map(|x| x.1)
+        .ok_or(anyhow::anyhow!("column {} not found in {}", column, table))?;
+    let values = values
+        .iter()
+        .map(|x| coerce_filter_value(column, &col_def, &json_force_str(x)))
+        .collect::<Result<Vec<_>>>()?;
+    let cond = Condition::all().add(Expr::col(Alias::new(column)).is_in(values));
+    let (_, rows) = model
+        .list(
+            admin.get_connection(),
+            &ListParam {
+                cond,
+                ordering: vec![],
+                offset: None,
+                limit: None,
+                count: false,
+                rank: None,
+            },
+        )
+        .await?;
+    rows.iter()
+        .map(|row| {
+            let value = row.get(column).map(json_force_str).unwrap_or_default();
+            Ok((value, model.to_str(row)?))
+        })
+        .collect()
+}
+
+async fn get_facet_choices(
+    admin: &Admin,
+    user: &AdminUser,
+    model: &(dyn ModelAdminTrait + Send + Sync),
+    name: &str,
+    col_def: &ColumnDef,
+    base: &Condition,
+) -> Result<(Vec<FacetChoice>, bool)> {
+    let conn = admin.get_connection();
+    let columns = model.get_columns();
+    let fields = model.get_form_fields();
+    let field = fields.iter().find_map(|x| match x {
+        AdminField::Field(f) if f.fields() == vec![name.to_string()] => Some(f),
+        _ => None,
+    });
+    let exact = |value: &str| vec![(name.to_string(), value.to_string())];
+
+    if is_date_column(col_def) && !date_ranges(name).is_empty() {
+        let mut choices = Vec::new();
+        for (label, params) in date_ranges(name) {
+            let mut filter: HashMap<String, Vec<String>> = HashMap::new();
+            for (key, value) in params.iter() {
+                filter.entry(key.clone()).or_default().push(value.clone());
+            }
+            let cond = base
+                .clone()
+                .add(create_cond_from_hash_map(&columns, &filter)?);
+            let count = admin.list_total(model, &cond).await?.value();
+            choices.push(FacetChoice {
+                label,
+                params,
+                count,
+            });
+        }
+        return Ok((choices, false));
+    }
+
+    let counts = model
+        .count_by(conn, base, name, FACET_CHOICES_LIMIT)
+        .await?;
+    let null_count = counts
+        .iter()
+        .filter(|x| x.0.is_null())
+        .map(|x| x.1)
+        .sum::<u64>();
+    let values: Vec<Json> = counts
+        .iter()
+        .filter(|x| !x.0.is_null())
+        .map(|x| x.0.clone())
+        .collect();
+    let counts: HashMap<String, u64> = counts
+        .iter()
+        .filter(|x| !x.0.is_null())
+        .map(|(value, count)| (json_force_str(value), *count))
+        .collect();
+
+    let enum_choices = match col_def.get_column_type() {
+        ColumnType::Enum { variants, .. } => Some(
+            variants
+                .iter()
+                .map(|x| (x.to_string(), x.to_string()))
+                .collect::<Vec<_>>(),
+        ),
+        _ => field.and_then(|x| x.choices()),
+    };
+
+    let (mut choices, searchable) = if let ColumnType::Boolean = col_def.get_column_type() {
+        let choices = [("true", "yes"), ("false", "no")]
+            .into_iter()
+            .map(|(value, label)| FacetChoice {
+                label: label.into(),
+                params: exact(value),
+                count: Some(counts.get(value).cloned().unwrap_or(0)),
+            })
+            .collect();
+        (choices, false)
+    } else if let Some(enum_choices) = enum_choices {
+        let choices = enum_choices
+            .into_iter()
+            .map(|(value, label)| FacetChoice {
+                label,
+                params: exact(&value),
+                count: Some(counts.get(&value).cloned().unwrap_or(0)),
+            })
+            .collect();
+        (choices, false)
+    } else if let Some((table, column)) = field.and_then(|x| x.foreign_key()) {
+        let labels = foreign_key_labels(admin, user, &table, &column, &values).await?;
+        let mut choices: Vec<_> = counts
+            .into_iter()
+            .map(|(value, count)| FacetChoice {
+                label: labels.get(&value).cloned().unwrap_or_else(|| value.clone()),
+                params: exact(&value),
+                count: Some(count),
+            })
+            .collect();
+        choices.sort_by(|a, b| a.label.cmp(&b.label));
+        (choices, true)
+    } else {
+        let mut choices: Vec<_> = counts
+            .into_iter()
+            .map(|(value, count)| FacetChoice {
+                label: value.clone(),
+                params: exact(&value),
+                count: Some(count),
+            })
+            .collect();
+        choices.sort_by(|a, b| a.label.cmp(&b.label));
+        (choices, false)
+    };
+
+    if col_def.is_null() {
+        choices.push(FacetChoice {
+            label: "unknown".into(),
+            params: vec![(format!("{}__isnull", name), "true".into())],
+            count: Some(null_count),
+        });
+    }
+    Ok((choices, searchable))
+}
+
+/// Facets for `list_filter`, counted within the current filters except the facet's own.
+/// Columns list their `FACET_CHOICES_LIMIT` most frequent values, date columns are counted
+/// following the count strategy of the model.
+pub async fn get_list_filters(
+    admin: &Admin,
+    user: &AdminUser,
+    model: &(dyn ModelAdminTrait + Send + Sync),
+    query_param: &HashMap<String, Vec<String>>,
+) -> Result<Vec<AdminListFilter>> {
+    let columns = model.get_columns();
+    let mut filters = Vec::new();
+    for name in model.list_filter() {
+        let col_def = columns
+            .iter()
+            .find(|x| x.0 == name)
+            .map(|x| x.1.clone())
+            .ok_or(anyhow::anyhow!("list_filter column {} not found", name))?;
+
+        let others: HashMap<String, Vec<String>> = query_param
+            .iter()
+            .filter(|(k, _)| split_lookup(k).0 != name && *k != "_p" && *k != "_msg")
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let mut current: Vec<(String, String)> = query_param
+            .iter()
+            .filter(|(k, _)| !k.starts_with('_') && split_lookup(k).0 == name)
+            .flat_map(|(k, v)| v.iter().map(|x| (k.clone(), x.clone())))
+            .collect();
+        current.sort();
+
+        let base_query = parse_query(&others, model.get_list_per_page())?;
+        let base = list_query_to_list_param(
+            &base_query,
+            model,
+            admin.get_connection().get_database_backend(),
+        )?
+        .cond;
+        let (choices, searchable) =
+            get_facet_choices(admin, user, model, &name, &col_def, &base).await?;
+
+        let mut options = vec![AdminListFilterOption {
+            label: "all".into(),
+            count: None,
+            link: to_link(&others, &[]),
+            is_active: current.is_empty(),
+        }];
+        for mut choice in choices {
+            let link = to_link(&others, &choice.params);
+            choice.params.sort();
+            options.push(AdminListFilterOption {
+                label: choice.label,
+                count: choice.count,
+                link,
+                is_active: choice.params == current,
+            });
+        }
+        filters.push(AdminListFilter {
+            name,
+            searchable,
+            options,
+        });
+    }
+    Ok(filters)
+}
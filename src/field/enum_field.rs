@@ -1,5 +1,9 @@
 use super::{AdminField, FieldTrait};
-use crate::{json_force_str, templates::AdminFormSelect, Admin, Json, Result};
+use crate::{
+    json_force_str,
+    templates::{AdminDisplayValue, AdminFormSelect},
+    Admin, AdminUser, Json, Result,
+};
 use askama::DynTemplate;
 use async_trait::async_trait;
 use sea_orm::ColumnTrait;
@@ -58,6 +62,10 @@ impl FieldTrait for EnumField {
         vec![self.0.name.clone()]
     }
 
+    fn choices(&self) -> Option<Vec<(String, String)>> {
+        Some(self.0.choices.clone())
+    }
+
     async fn get_template(
         &self,
         _admin: &Admin,
@@ -72,4 +80,25 @@ impl FieldTrait for EnumField {
         template.disabled = disabled;
         Ok(Box::new(template))
     }
+
+    /// The label of the choice instead of the stored value.
+    async fn get_display(
+        &self,
+        _admin: &Admin,
+        row: &Json,
+        _user: &AdminUser,
+    ) -> Result<Box<dyn DynTemplate + Send>> {
+        let value = json_force_str(row.get(&self.0.name).unwrap_or(&Json::Null));
+        Ok(Box::new(AdminDisplayValue {
+            label: self.0.label.clone(),
+            value: self
+                .0
+                .choices
+                .iter()
+                .find(|(choice, _)| *choice == value)
+                .map(|(_, label)| label.clone())
+                .unwrap_or(value),
+            link: None,
+        }))
+    }
 }
@@ -3,8 +3,8 @@ use std::collections::HashMap;
 use super::FieldTrait;
 use crate::{
     create_cond_from_json, json_force_str,
-    templates::{self, AdminFormAutoComplete},
-    Admin, CustomError, Json, Result,
+    templates::{self, AdminDisplayValue, AdminFormAutoComplete},
+    Admin, AdminUser, CustomError, Json, ModelAdminTrait, Result,
 };
 use askama::DynTemplate;
 use async_trait::async_trait;
@@ -86,6 +86,37 @@ where
 }
 
 impl ForeignKeyField {
+    /// Row referenced by `parent_value`, `None` when the key is null or the row is missing.
+    async fn get_target<'a>(
+        &self,
+        admin: &'a Admin,
+        parent_value: &Json,
+    ) -> Result<(&'a Box<dyn ModelAdminTrait + Send + Sync>, Option<Json>)> {
+        let tm = admin
+            .get_model(&self.0.to_table)
+            .ok_or(CustomError::new("no table found"))?;
+        let m: serde_json::Map<String, Json> = self
+            .0
+            .cols
+            .iter()
+            .map(|k| (k.to_col.clone(), parent_value.get(&k.from_col)))
+            .filter(|x| x.1.filter(|x| !x.is_null()).is_some())
+            .map(|x| (x.0, x.1.unwrap().clone()))
+            .collect();
+        if m.is_empty() {
+            return Ok((tm, None));
+        }
+        let cond = create_cond_from_json(
+            &tm.get_columns().iter().map(|x| x.0.clone()).collect(),
+            &Json::Object(m),
+            false,
+        )?;
+        Ok((
+            tm,
+            tm.get(admin.get_connection(), &cond).await.unwrap_or(None),
+        ))
+    }
+
     pub fn new(rel_def: &RelationDef, nullable: bool) -> Result<Self> {
         Ok(ForeignKeyField(AdminFormAutoComplete {
             prefix: "".to_string(),
@@ -108,6 +139,13 @@ impl FieldTrait for ForeignKeyField {
         self.0.cols.iter().map(|x| x.from_col.clone()).collect()
     }
 
+    fn foreign_key(&self) -> Option<(String, String)> {
+        match self.0.cols.as_slice() {
+            [col] => Some((self.0.to_table.clone(), col.to_col.clone())),
+            _ => None,
+        }
+    }
+
     async fn get_template(
         &self,
         admin: &Admin,
@@ -117,23 +155,7 @@ impl FieldTrait for ForeignKeyField {
     ) -> Result<Box<dyn DynTemplate + Send>> {
         let mut template = self.0.clone();
         if let Some(parent_value) = parent_value {
-            let tm = admin
-                .get_model(&self.0.to_table)
-                .ok_or(CustomError::new("no table found"))?;
-            let m: serde_json::Map<String, Json> = self
-                .0
-                .cols
-                .iter()
-                .map(|k| (k.to_col.clone(), parent_value.get(&k.from_col)))
-                .filter(|x| x.1.filter(|x| !x.is_null()).is_some())
-                .map(|x| (x.0, x.1.unwrap().clone()))
-                .collect();
-            let cond = create_cond_from_json(
-                &tm.get_columns().iter().map(|x| x.0.clone()).collect(),
-                &Json::Object(m),
-                false,
-            )?;
-            let tr = tm.get(&admin.get_connection(), &cond).await.unwrap_or(None);
+            let (tm, tr) = self.get_target(admin, parent_value).await?;
             template.cols = template
                 .cols
                 .iter()
@@ -159,4 +181,39 @@ impl FieldTrait for ForeignKeyField {
         template.disabled = disabled;
         Ok(Box::new(template))
     }
+    /// The `to_str` label of the referenced row, linked to its detail page.
+    /// The key values are shown instead when `user` can not view the referenced model.
+    async fn get_display(
+        &self,
+        admin: &Admin,
+        row: &Json,
+        user: &AdminUser,
+    ) -> Result<Box<dyn DynTemplate + Send>> {
+        let mut template = AdminDisplayValue {
+            label: self.0.label.clone(),
+            value: self
+                .fields()
+                .iter()
+                .map(|x| json_force_str(row.get(x).unwrap_or(&Json::Null)))
+                .join(", "),
+            link: None,
+        };
+        let viewable = admin
+            .get_model(&self.0.to_table)
+            .is_some_and(|x| x.has_view_permission(user));
+        if !viewable {
+            return Ok(Box::new(template));
+        }
+        let (tm, target) = self.get_target(admin, row).await?;
+        if let Some(target) = target {
+            template.value = tm.to_str(&target)?;
+            template.link = Some(format!(
+                "{}/{}/view/{}/",
+                admin.sub_path(),
+                tm.get_table_name(),
+                tm.json_to_key(&target)?
+            ));
+        }
+        Ok(Box::new(template))
+    }
 }
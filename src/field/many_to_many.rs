@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use super::{foreign_key_field::extract_table_name, AdminField, RelationTrait};
+use super::{display_table, foreign_key_field::extract_table_name, AdminField, RelationTrait};
 use crate::field::foreign_key_field::identity_to_vec_string;
 use crate::templates::{
     AdminFormAutoComplete, AdminFormAutoCompleteChoice, AdminFormAutoCompleteCol,
@@ -9,7 +9,7 @@ use crate::{
     create_cond_from_input_json, create_cond_from_json, extract_cols_from_relation_def,
     json_force_str, ListParam, ModelAdminTrait,
 };
-use crate::{Admin, Json, Result};
+use crate::{Admin, AdminUser, AuditAction, Json, Result};
 use askama::DynTemplate;
 use async_trait::async_trait;
 use sea_orm::{Condition, DatabaseConnection, DatabaseTransaction, RelationDef};
@@ -46,6 +46,72 @@ impl ManyToMany {
             .collect())
     }
 
+    /// Rows of the target table linked to `parent_object`, with the model admin of the target table.
+    async fn list_targets<'a>(
+        &self,
+        admin: &'a Admin,
+        parent_object: &serde_json::Map<String, Json>,
+    ) -> Result<(&'a Box<dyn ModelAdminTrait + Send + Sync>, Vec<Json>)> {
+        let model = admin
+            .get_model(&extract_table_name(&self.from_def.from_tbl)?)
+            .ok_or(anyhow::anyhow!(
+                "table not found {:?}. Please add tbl to admin",
+                extract_table_name(&self.from_def.from_tbl)
+            ))?;
+
+        let cur_list = self
+            .list_related(model, admin.get_connection(), parent_object)
+            .await?;
+
+        let to_columns = identity_to_vec_string(&self.to_def.to_col);
+        let mut cond = Condition::any();
+        for row in cur_list.into_iter() {
+            let mut filter: serde_json::Map<String, Json> = serde_json::Map::new();
+            for (fr, to) in identity_to_vec_string(&self.to_def.from_col)
+                .into_iter()
+                .zip(identity_to_vec_string(&self.to_def.to_col))
+            {
+                filter.insert(
+                    to,
+                    row.get(&fr)
+                        .ok_or(anyhow::anyhow!("key not found"))?
+                        .clone(),
+                );
+            }
+            cond = cond.add(create_cond_from_json(
+                &to_columns,
+                &Json::Object(filter),
+                true,
+            )?);
+        }
+
+        let to_model = admin
+            .get_model(&extract_table_name(&self.to_def.to_tbl)?)
+            .ok_or(anyhow::anyhow!(
+                "table not found: {:?}. Please add tbl to admin",
+                extract_table_name(&self.to_def.to_tbl)
+            ))?;
+
+        let (_size, related) = if cond.is_empty() {
+            (0, vec![])
+        } else {
+            to_model
+                .list(
+                    admin.get_connection(),
+                    &ListParam {
+                        cond,
+                        ordering: vec![],
+                        offset: None,
+                        limit: None,
+                        count: false,
+                        rank: None,
+                    },
+                )
+                .await?
+        };
+        Ok((to_model, related))
+    }
+
     async fn list_related(
         &self,
         model: &Box<dyn ModelAdminTrait + Send + Sync>,
@@ -67,6 +133,8 @@ impl ManyToMany {
                     ordering: vec![],
                     offset: None,
                     limit: None,
+                    count: false,
+                    rank: None,
                 },
             )
             .await?;
@@ -107,6 +175,10 @@ fn check_exists(key: &Vec<String>, source: &Vec<Vec<String>>) -> bool {
 
 #[async_trait]
 impl RelationTrait for ManyToMany {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
     fn related_tables(&self) -> Result<HashSet<String>> {
         vec![
             extract_table_name(&self.from_def.to_tbl),
@@ -142,61 +214,7 @@ impl RelationTrait for ManyToMany {
                 .as_object()
                 .ok_or(anyhow::anyhow!("invalid json => {:?}", parent_value))?;
 
-            let model = admin
-                .get_model(&extract_table_name(&self.from_def.from_tbl)?)
-                .ok_or(anyhow::anyhow!(
-                    "table not found {:?}. Please add tbl to admin",
-                    extract_table_name(&self.from_def.from_tbl)
-                ))?;
-
-            let cur_list = self
-                .list_related(model, admin.get_connection(), parent_object)
-                .await?;
-
-            let to_columns = identity_to_vec_string(&self.to_def.to_col);
-            let mut cond = Condition::any();
-            for row in cur_list.into_iter() {
-                let mut filter: serde_json::Map<String, Json> = serde_json::Map::new();
-                for (fr, to) in identity_to_vec_string(&self.to_def.from_col)
-                    .into_iter()
-                    .zip(identity_to_vec_string(&self.to_def.to_col).into_iter())
-                {
-                    filter.insert(
-                        to,
-                        row.get(&fr)
-                            .ok_or(anyhow::anyhow!("key not found"))?
-                            .clone(),
-                    );
-                }
-                cond = cond.add(create_cond_from_json(
-                    &to_columns,
-                    &Json::Object(filter),
-                    true,
-                )?);
-            }
-
-            let to_model = admin
-                .get_model(&extract_table_name(&self.to_def.to_tbl)?)
-                .ok_or(anyhow::anyhow!(
-                    "table not found: {:?}. Please add tbl to admin",
-                    extract_table_name(&self.to_def.to_tbl)
-                ))?;
-
-            let (_size, related) = if cond.is_empty() {
-                (0, vec![])
-            } else {
-                to_model
-                    .list(
-                        admin.get_connection(),
-                        &ListParam {
-                            cond,
-                            ordering: vec![],
-                            offset: None,
-                            limit: None,
-                        },
-                    )
-                    .await?
-            };
+            let (to_model, related) = self.list_targets(admin, parent_object).await?;
 
             template.cols = template
                 .cols
@@ -233,10 +251,30 @@ impl RelationTrait for ManyToMany {
         Ok(Box::new(template))
     }
 
+    async fn get_display(
+        &self,
+        admin: &Admin,
+        parent_value: &Json,
+        user: &AdminUser,
+    ) -> Result<Box<dyn DynTemplate + Send>> {
+        let parent_object = parent_value
+            .as_object()
+            .ok_or(anyhow::anyhow!("invalid json => {:?}", parent_value))?;
+        let (to_model, related) = self.list_targets(admin, parent_object).await?;
+        Ok(Box::new(display_table(
+            admin,
+            &self.name,
+            to_model.as_ref(),
+            &related,
+            user,
+        )?))
+    }
+
     async fn commit(
         &self,
         admin: &Admin,
         parent_value: &Json,
+        user: &AdminUser,
         txn: &DatabaseTransaction,
     ) -> Result<Json> {
         let parent_object = parent_value
@@ -303,7 +341,10 @@ impl RelationTrait for ManyToMany {
                 });
             let value = Json::Object(value);
             // println!("inserting {:?}", value);
-            model.insert(txn, &value).await?;
+            let r = model.insert(txn, &value).await?;
+            admin
+                .audit(model, AuditAction::Create, user, None, Some(r), txn)
+                .await?;
         }
 
         let through_columns: Vec<_> = model.get_columns();
@@ -317,7 +358,16 @@ impl RelationTrait for ManyToMany {
             let value = Json::Object(value);
             let cond = create_cond_from_input_json(&through_columns, &value, false)?;
             // println!("deleting {:?}", value);
-            model.delete(txn, &cond).await?;
+            let before = if admin.has_audit_log() {
+                model.get_with_txn(txn, &cond).await?
+            } else {
+                None
+            };
+            if model.delete(txn, &cond).await? > 0 {
+                admin
+                    .audit(model, AuditAction::Delete, user, before, None, txn)
+                    .await?;
+            }
         }
         Ok(Json::Null)
     }
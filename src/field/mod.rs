@@ -13,7 +13,11 @@ mod tool;
 use std::collections::HashSet;
 
 use super::{Json, Result};
-use crate::Admin;
+use crate::{
+    json_force_str,
+    templates::{AdminDisplayTable, AdminDisplayValue},
+    Admin, AdminUser, ModelAdminTrait,
+};
 use askama::DynTemplate;
 use async_trait::async_trait;
 pub use checkbox_field::CheckboxField;
@@ -21,7 +25,8 @@ pub use date_time_field::DateTimeField;
 pub use default_field::get_default_field;
 pub use enum_field::{enum_field, EnumField};
 pub use foreign_key_field::{
-    extract_cols_from_relation_def, relation_def_is_nullable, ForeignKeyField,
+    extract_cols_from_relation_def, extract_table_name, identity_to_vec_string,
+    relation_def_is_nullable, ForeignKeyField,
 };
 pub use input_field::InputField;
 pub use many_to_many::{m2m_field, ManyToMany};
@@ -36,6 +41,14 @@ pub enum AdminField {
 }
 
 impl AdminField {
+    /// Columns of a field or the name of a relation, matched against `Fieldset::fields`.
+    pub fn names(&self) -> Vec<String> {
+        match &self {
+            AdminField::Field(f) => f.fields(),
+            AdminField::Relation(r) => vec![r.name()],
+        }
+    }
+
     pub async fn get_template(
         &self,
         admin: &Admin,
@@ -48,11 +61,54 @@ impl AdminField {
             AdminField::Relation(f) => f.get_template(admin, parent_value, prefix, disabled).await,
         }
     }
+
+    /// Read-only rendering of the field for the detail page.
+    /// Rows of other models are only labeled and linked when `user` can view them.
+    pub async fn get_display(
+        &self,
+        admin: &Admin,
+        row: &Json,
+        user: &AdminUser,
+    ) -> Result<Box<dyn DynTemplate + Send>> {
+        match &self {
+            AdminField::Field(f) => f.get_display(admin, row, user).await,
+            AdminField::Relation(f) => f.get_display(admin, row, user).await,
+        }
+    }
+}
+
+/// Titled group of the form, `fields` holds column names and names of inline relations in display order.
+#[derive(Debug, Clone)]
+pub struct Fieldset {
+    pub title: String,
+    pub fields: Vec<String>,
+    pub collapsed: bool,
+}
+
+impl Fieldset {
+    pub fn new(title: &str, fields: Vec<String>, collapsed: bool) -> Self {
+        Fieldset {
+            title: title.into(),
+            fields,
+            collapsed,
+        }
+    }
 }
 
 #[async_trait]
 pub trait FieldTrait {
     fn fields(&self) -> Vec<String>;
+
+    /// Fixed `(value, label)` choices of the field, used by the list filter.
+    fn choices(&self) -> Option<Vec<(String, String)>> {
+        None
+    }
+
+    /// `(table, column)` referenced by a single column foreign key.
+    fn foreign_key(&self) -> Option<(String, String)> {
+        None
+    }
+
     async fn get_template(
         &self,
         admin: &Admin,
@@ -60,10 +116,38 @@ pub trait FieldTrait {
         prefix: &str,
         disabled: bool,
     ) -> Result<Box<dyn DynTemplate + Send>>;
+
+    /// Values of the columns of the field as text.
+    async fn get_display(
+        &self,
+        _admin: &Admin,
+        row: &Json,
+        _user: &AdminUser,
+    ) -> Result<Box<dyn DynTemplate + Send>> {
+        Ok(display_columns(&self.fields(), row))
+    }
+}
+
+/// Label and text of `columns` of `row`, used for columns without a field.
+pub fn display_columns(columns: &[String], row: &Json) -> Box<dyn DynTemplate + Send> {
+    Box::new(AdminDisplayValue {
+        label: columns.join(", "),
+        value: columns
+            .iter()
+            .map(|x| json_force_str(row.get(x).unwrap_or(&Json::Null)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        link: None,
+    })
 }
 
 #[async_trait]
 pub trait RelationTrait {
+    /// Name matched against `Fieldset::fields`, unnamed relations are shown after the fieldsets.
+    fn name(&self) -> String {
+        String::new()
+    }
+
     fn related_tables(&self) -> Result<HashSet<String>>;
 
     async fn get_template(
@@ -74,10 +158,67 @@ pub trait RelationTrait {
         disabled: bool,
     ) -> Result<Box<dyn DynTemplate + Send>>;
 
+    /// Related rows as a read-only table, empty by default.
+    async fn get_display(
+        &self,
+        _admin: &Admin,
+        _parent_value: &Json,
+        _user: &AdminUser,
+    ) -> Result<Box<dyn DynTemplate + Send>> {
+        Ok(Box::new(AdminDisplayTable {
+            name: self.name(),
+            keys: vec![],
+            rows: vec![],
+            hidden_rows: 0,
+        }))
+    }
+
     async fn commit(
         &self,
         admin: &Admin,
         parent_value: &Json,
+        user: &AdminUser,
         txn: &DatabaseTransaction,
     ) -> Result<Json>;
 }
+
+/// `rows` of `model` with the columns of its list, each row linking to its detail page.
+/// Only the number of rows is shown when `user` can not view `model`.
+pub fn display_table(
+    admin: &Admin,
+    name: &str,
+    model: &(dyn ModelAdminTrait + Send + Sync),
+    rows: &[Json],
+    user: &AdminUser,
+) -> Result<AdminDisplayTable> {
+    if !model.has_view_permission(user) {
+        return Ok(AdminDisplayTable {
+            name: name.into(),
+            keys: vec![],
+            rows: vec![],
+            hidden_rows: rows.len(),
+        });
+    }
+    let keys = model.list_display();
+    Ok(AdminDisplayTable {
+        name: name.into(),
+        rows: rows
+            .iter()
+            .map(|row| {
+                Ok((
+                    format!(
+                        "{}/{}/view/{}/",
+                        admin.sub_path(),
+                        model.get_table_name(),
+                        model.json_to_key(row)?
+                    ),
+                    keys.iter()
+                        .map(|key| json_force_str(row.get(key).unwrap_or(&Json::Null)))
+                        .collect(),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?,
+        keys,
+        hidden_rows: 0,
+    })
+}
@@ -1,12 +1,13 @@
 use super::AdminField;
 use super::{
+    display_table,
     foreign_key_field::{extract_table_name, identity_to_vec_string},
     RelationTrait,
 };
 use crate::{create_cond_from_json, json_extract_prefixed};
 use crate::{
     templates::{RelationForm, RelationFormRow, RelationFormRowField},
-    Admin, Json, Result,
+    Admin, AdminUser, Json, ModelAdminTrait, Result,
 };
 use askama::DynTemplate;
 use async_trait::async_trait;
@@ -31,10 +32,51 @@ impl Relation {
             multiple,
         }
     }
+
+    /// Rows of the related table referencing `parent_value`.
+    async fn list_related(
+        &self,
+        admin: &Admin,
+        model: &(dyn ModelAdminTrait + Send + Sync),
+        parent_value: &Json,
+    ) -> Result<Vec<Json>> {
+        let m: serde_json::Map<String, Json> = identity_to_vec_string(&self.def.from_col)
+            .into_iter()
+            .zip(identity_to_vec_string(&self.def.to_col))
+            .map(|(fr, to)| (fr.clone(), parent_value.get(&to)))
+            .filter(|x| x.1.filter(|x| !x.is_null()).is_some())
+            .map(|x| (x.0, x.1.unwrap().clone()))
+            .collect();
+
+        let cond = create_cond_from_json(
+            &model.get_columns().iter().map(|x| x.0.clone()).collect(),
+            &Json::Object(m),
+            false,
+        )?;
+
+        let (_, jv_list) = model
+            .list(
+                admin.get_connection(),
+                &crate::ListParam {
+                    cond,
+                    ordering: vec![],
+                    offset: None,
+                    limit: None,
+                    count: false,
+                    rank: None,
+                },
+            )
+            .await?;
+        Ok(jv_list)
+    }
 }
 
 #[async_trait]
 impl RelationTrait for Relation {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
     fn related_tables(&self) -> Result<HashSet<String>> {
         vec![
             extract_table_name(&self.def.to_tbl),
@@ -62,30 +104,8 @@ impl RelationTrait for Relation {
         let pkeys: HashSet<String> = model.get_primary_keys().into_iter().collect();
         let mut rows = vec![];
         if let Some(parent_value) = parent_value {
-            let m: serde_json::Map<String, Json> = identity_to_vec_string(&self.def.from_col)
-                .into_iter()
-                .zip(identity_to_vec_string(&self.def.to_col).into_iter())
-                .map(|(fr, to)| (fr.clone(), parent_value.get(&to)))
-                .filter(|x| x.1.filter(|x| !x.is_null()).is_some())
-                .map(|x| (x.0, x.1.unwrap().clone()))
-                .collect();
-
-            let cond = create_cond_from_json(
-                &model.get_columns().iter().map(|x| x.0.clone()).collect(),
-                &Json::Object(m),
-                false,
-            )?;
-
-            let (_, jv_list) = model
-                .list(
-                    &admin.get_connection(),
-                    &crate::ListParam {
-                        cond: cond,
-                        ordering: vec![],
-                        offset: None,
-                        limit: None,
-                    },
-                )
+            let jv_list = self
+                .list_related(admin, model.as_ref(), parent_value)
                 .await?;
 
             for (i, jv) in jv_list.iter().enumerate() {
@@ -180,10 +200,32 @@ impl RelationTrait for Relation {
         }))
     }
 
+    async fn get_display(
+        &self,
+        admin: &Admin,
+        parent_value: &Json,
+        user: &AdminUser,
+    ) -> Result<Box<dyn DynTemplate + Send>> {
+        let model = admin
+            .get_model(&extract_table_name(&self.def.from_tbl)?)
+            .ok_or(anyhow::anyhow!("no related table found"))?;
+        let rows = self
+            .list_related(admin, model.as_ref(), parent_value)
+            .await?;
+        Ok(Box::new(display_table(
+            admin,
+            &self.name,
+            model.as_ref(),
+            &rows,
+            user,
+        )?))
+    }
+
     async fn commit(
         &self,
         admin: &Admin,
         parent_value: &Json,
+        user: &AdminUser,
         txn: &DatabaseTransaction,
     ) -> Result<Json> {
         let parent_object = parent_value
@@ -213,15 +255,15 @@ impl RelationTrait for Relation {
                             }
                         });
                     // println!("create {:?}", data);
-                    admin.create(model, &data, Some(txn)).await?;
+                    admin.create(model, &data, user, Some(txn)).await?;
                 }
                 &"U" => {
                     //println!("update {:?}", data);
-                    admin.update(model, &data, Some(txn)).await?;
+                    admin.update(model, &data, user, Some(txn)).await?;
                 }
                 &"D" => {
                     // println!("delete {:?}", data);
-                    admin.delete(model, &data, Some(txn)).await?;
+                    admin.delete(model, &data, user, Some(txn)).await?;
                 }
                 &"I" => {
                     // skip
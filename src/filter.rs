@@ -1,38 +1,248 @@
-use crate::{Json, ListParam, ListQuery, Result};
-use sea_orm::sea_query::{Alias, Condition, Expr, SeaRc};
-use sea_orm::{ColumnDef, DynIden, EntityTrait, Iden, QueryOrder, Select};
+use crate::{json_force_str, AdminError, Json, ListParam, ListQuery, ModelAdminTrait, Result};
+use base64::Engine;
+use sea_orm::sea_query::{Alias, Condition, Expr, Func, LikeExpr, SeaRc, SimpleExpr};
+use sea_orm::{
+    ColumnDef, ColumnType, DatabaseConnection, DbBackend, DynIden, EntityTrait, Iden,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select, Value,
+};
 use std::collections::HashMap;
 
-// enum ConditionOp {
-//     Eq,
-//     // Like,
-//     // Gt,
-//     // Gte,
-//     // Lt,
-//     // Lte,
-// }
-
-// fn get_condition<C>(col: &C, op: &ConditionOp, value: &str) -> Condition
-// where
-//     C: ColumnTrait,
-// {
-//     match op {
-//         ConditionOp::Eq => Expr::col(*col).eq(value).into_condition(),
-//     }
-// }
+/// How the `_q` search words are matched against the search fields of a model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Every word is contained (`LIKE '%word%'`) in one of the text fields, or equal to a number field.
+    Contains,
+    /// Postgres full-text search over the text fields, `to_tsvector(..) @@ plainto_tsquery(..)`,
+    /// ordered by relevance unless the list has an `_o` ordering. Other databases use `Contains`.
+    FullText,
+}
+
+/// Django style lookup given as suffix of the query key, e.g. `age__gte=18`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookup {
+    Exact,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Contains,
+    IContains,
+    StartsWith,
+    EndsWith,
+    In,
+    IsNull,
+    Range,
+}
+
+impl Lookup {
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        Some(match suffix {
+            "exact" => Lookup::Exact,
+            "gt" => Lookup::Gt,
+            "gte" => Lookup::Gte,
+            "lt" => Lookup::Lt,
+            "lte" => Lookup::Lte,
+            "contains" => Lookup::Contains,
+            "icontains" => Lookup::IContains,
+            "startswith" => Lookup::StartsWith,
+            "endswith" => Lookup::EndsWith,
+            "in" => Lookup::In,
+            "isnull" => Lookup::IsNull,
+            "range" => Lookup::Range,
+            _ => return None,
+        })
+    }
+}
+
+/// Splits `name__icontains` into `("name", Lookup::IContains)`, keys without a known suffix are `Exact`.
+pub fn split_lookup(key: &str) -> (&str, Lookup) {
+    if let Some((col, suffix)) = key.rsplit_once("__") {
+        if let Some(lookup) = Lookup::from_suffix(suffix) {
+            return (col, lookup);
+        }
+    }
+    (key, Lookup::Exact)
+}
+
+fn parse_filter_value<T: std::str::FromStr>(col_name: &str, value: &str) -> Result<T> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| anyhow::anyhow!("invalid value {:?} for {}", value, col_name))
+}
+
+#[cfg(feature = "with-chrono")]
+fn parse_filter_datetime(col_name: &str, value: &str) -> Result<chrono::NaiveDateTime> {
+    let value = value.trim();
+    [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|f| chrono::NaiveDateTime::parse_from_str(value, f).ok())
+    .or_else(|| {
+        chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|x| x.and_hms_opt(0, 0, 0))
+    })
+    .ok_or(anyhow::anyhow!(
+        "invalid value {:?} for {}",
+        value,
+        col_name
+    ))
+}
+
+/// Converts a query string value to the type of the column.
+pub fn coerce_filter_value(col_name: &str, col_def: &ColumnDef, value: &str) -> Result<Value> {
+    Ok(match col_def.get_column_type() {
+        ColumnType::TinyInteger
+        | ColumnType::SmallInteger
+        | ColumnType::Integer
+        | ColumnType::BigInteger
+        | ColumnType::TinyUnsigned
+        | ColumnType::SmallUnsigned
+        | ColumnType::Unsigned
+        | ColumnType::BigUnsigned => parse_filter_value::<i64>(col_name, value)?.into(),
+        ColumnType::Float | ColumnType::Double => {
+            parse_filter_value::<f64>(col_name, value)?.into()
+        }
+        #[cfg(feature = "with-rust_decimal")]
+        ColumnType::Decimal(_) | ColumnType::Money(_) => {
+            parse_filter_value::<rust_decimal::Decimal>(col_name, value)?.into()
+        }
+        ColumnType::Boolean => match value.trim() {
+            "true" | "1" => true.into(),
+            "false" | "0" => false.into(),
+            _ => Err(anyhow::anyhow!(
+                "invalid value {:?} for {}",
+                value,
+                col_name
+            ))?,
+        },
+        #[cfg(feature = "with-uuid")]
+        ColumnType::Uuid => parse_filter_value::<uuid::Uuid>(col_name, value)?.into(),
+        #[cfg(feature = "with-chrono")]
+        ColumnType::Date => parse_filter_value::<chrono::NaiveDate>(col_name, value)?.into(),
+        #[cfg(feature = "with-chrono")]
+        ColumnType::Time => parse_filter_value::<chrono::NaiveTime>(col_name, value)?.into(),
+        #[cfg(feature = "with-chrono")]
+        ColumnType::DateTime | ColumnType::Timestamp => {
+            parse_filter_datetime(col_name, value)?.into()
+        }
+        #[cfg(feature = "with-chrono")]
+        ColumnType::TimestampWithTimeZone => {
+            match chrono::DateTime::parse_from_rfc3339(value.trim()) {
+                Ok(v) => v.into(),
+                Err(_) => parse_filter_datetime(col_name, value)?
+                    .and_utc()
+                    .fixed_offset()
+                    .into(),
+            }
+        }
+        ColumnType::Binary(_) | ColumnType::VarBinary(_) | ColumnType::Blob => {
+            base64::engine::general_purpose::STANDARD
+                .decode(value.trim())
+                .map_err(|_| anyhow::anyhow!("invalid value {:?} for {}", value, col_name))?
+                .into()
+        }
+        _ => value.to_string().into(),
+    })
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn create_lookup_cond(
+    col_name: &str,
+    col_def: &ColumnDef,
+    lookup: Lookup,
+    value: &str,
+) -> Result<SimpleExpr> {
+    let col: DynIden = SeaRc::new(Alias::new(col_name));
+    let coerce = |x: &str| coerce_filter_value(col_name, col_def, x);
+    let like = |pattern: String| LikeExpr::new(pattern).escape('\\');
+    // LIKE is only defined for text, postgres rejects it on other types
+    let is_text = matches!(
+        col_def.get_column_type(),
+        ColumnType::Char(_) | ColumnType::String(_) | ColumnType::Text
+    );
+    if !is_text
+        && matches!(
+            lookup,
+            Lookup::Contains | Lookup::IContains | Lookup::StartsWith | Lookup::EndsWith
+        )
+    {
+        return Err(AdminError::field(col_name, "text lookups need a text column").into());
+    }
+    Ok(match lookup {
+        Lookup::Exact => Expr::col(col).eq(coerce(value)?),
+        Lookup::Gt => Expr::col(col).gt(coerce(value)?),
+        Lookup::Gte => Expr::col(col).gte(coerce(value)?),
+        Lookup::Lt => Expr::col(col).lt(coerce(value)?),
+        Lookup::Lte => Expr::col(col).lte(coerce(value)?),
+        Lookup::Contains => Expr::col(col).like(like(format!("%{}%", escape_like(value)))),
+        Lookup::IContains => Expr::expr(Func::lower(Expr::col(col)))
+            .like(like(format!("%{}%", escape_like(&value.to_lowercase())))),
+        Lookup::StartsWith => Expr::col(col).like(like(format!("{}%", escape_like(value)))),
+        Lookup::EndsWith => Expr::col(col).like(like(format!("%{}", escape_like(value)))),
+        Lookup::In => Expr::col(col).is_in(
+            value
+                .split(',')
+                .filter(|x| !x.is_empty())
+                .map(coerce)
+                .collect::<Result<Vec<_>>>()?,
+        ),
+        Lookup::IsNull => match value.trim() {
+            "true" | "1" => Expr::col(col).is_null(),
+            "false" | "0" => Expr::col(col).is_not_null(),
+            _ => Err(anyhow::anyhow!(
+                "isnull of {} must be true or false",
+                col_name
+            ))?,
+        },
+        Lookup::Range => match value.split_once(',') {
+            Some((from, to)) => Expr::col(col).between(coerce(from)?, coerce(to)?),
+            None => Err(anyhow::anyhow!(
+                "range of {} must be two values separated by comma",
+                col_name
+            ))?,
+        },
+    })
+}
+
+/// Filters, search and page of `query` for the list of `model` on a `backend` database.
 pub fn list_query_to_list_param(
     query: &ListQuery,
-    columns: &Vec<(String, ColumnDef)>,
+    model: &(dyn ModelAdminTrait + Send + Sync),
+    backend: DbBackend,
 ) -> Result<ListParam> {
+    let columns = model.get_columns();
     let mut cond = Condition::all();
-    let c = create_cond_from_hash_map(
-        &columns.iter().map(|x| x.0.clone()).collect(),
-        &query.filter,
-    )?;
+    let c = create_cond_from_hash_map(&columns, &query.filter)?;
     if !c.is_empty() {
         cond = cond.add(c);
     }
-    let c = create_cond_from_search_queries(columns, &query.queries)?;
+
+    let search_fields = model.get_search_fields();
+    let search_columns: Vec<_> = columns
+        .into_iter()
+        .filter(|(name, _)| search_fields.contains(name))
+        .collect();
+    let mut rank = None;
+    let c = if model.get_search_mode() == SearchMode::FullText && backend == DbBackend::Postgres {
+        let (c, r) = create_cond_from_full_text_search(&search_columns, &query.queries)?;
+        if query.ordering.is_empty() {
+            rank = r;
+        }
+        c
+    } else {
+        create_cond_from_search_queries(&search_columns, &query.queries, backend)?
+    };
     if !c.is_empty() {
         cond = cond.add(c);
     }
@@ -42,9 +252,55 @@ pub fn list_query_to_list_param(
         ordering: query.ordering.clone(),
         offset: Some(query.offset),
         limit: Some(query.limit),
+        count: false,
+        rank,
     })
 }
 
+pub async fn count_rows<E>(conn: &DatabaseConnection, cond: &Condition) -> Result<u64>
+where
+    E: EntityTrait,
+    <E as EntityTrait>::Model: Sync,
+{
+    Ok(E::find().filter(cond.clone()).count(conn).await?)
+}
+
+/// Number of rows for each distinct value of `column`, i.e. `SELECT column, COUNT(*) .. GROUP BY column`,
+/// the `limit` most frequent values first.
+pub async fn count_rows_by<E>(
+    conn: &DatabaseConnection,
+    cond: &Condition,
+    column: &str,
+    limit: u64,
+) -> Result<Vec<(Json, u64)>>
+where
+    E: EntityTrait,
+{
+    let col = <E::Column as std::str::FromStr>::from_str(column)
+        .map_err(|_| anyhow::anyhow!("column {} not found", column))?;
+    E::find()
+        .select_only()
+        .column_as(col, "value")
+        .column_as(Expr::col(col).count(), "count")
+        .filter(cond.clone())
+        .group_by(col)
+        .order_by_desc(Expr::col(col).count())
+        .order_by_asc(col)
+        .limit(limit)
+        .into_json()
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|row| {
+            let count = row
+                .get("count")
+                .and_then(|x| x.as_u64())
+                .ok_or(anyhow::anyhow!("invalid count"))?;
+            Ok((row.get("value").cloned().unwrap_or(Json::Null), count))
+        })
+        .collect()
+}
+
 pub fn set_ordering<E>(
     qs: Select<E>,
     ordering: &Vec<(<E as EntityTrait>::Column, sea_orm::Order)>,
@@ -77,18 +333,22 @@ where
 }
 
 pub fn create_cond_from_hash_map(
-    columns: &Vec<String>,
+    columns: &[(String, ColumnDef)],
     filter: &HashMap<String, Vec<String>>,
 ) -> Result<Condition> {
     let mut cond = Condition::all();
 
-    for col in columns.iter() {
-        // TODO: support "like", "gt", etc..
-        if let Some(queries) = filter.get(col) {
+    let mut keys: Vec<_> = filter.keys().collect();
+    keys.sort();
+    for (col_name, col_def) in columns.iter() {
+        for key in keys.iter() {
+            let (name, lookup) = split_lookup(key);
+            if name != col_name {
+                continue;
+            }
             let mut pcond = Condition::any();
-            for value in queries {
-                let col: DynIden = SeaRc::new(Alias::new(col));
-                pcond = pcond.add(Expr::col(col).eq(value.clone()));
+            for value in filter[*key].iter() {
+                pcond = pcond.add(create_lookup_cond(col_name, col_def, lookup, value)?);
             }
             cond = cond.add(pcond);
         }
@@ -163,8 +423,7 @@ pub fn create_cond_from_input_json(
             match col_def.get_column_type() {
                 sea_orm::ColumnType::Char(_)
                 | sea_orm::ColumnType::String(_)
-                | sea_orm::ColumnType::Text
-                | sea_orm::ColumnType::Uuid => {
+                | sea_orm::ColumnType::Text => {
                     cond = cond.add(Expr::col(col).eq(value.as_str()));
                 }
                 sea_orm::ColumnType::TinyInteger
@@ -177,9 +436,13 @@ pub fn create_cond_from_input_json(
                 | sea_orm::ColumnType::BigUnsigned => {
                     cond = cond.add(Expr::col(col).eq(json_force_i64(value)?));
                 }
-                sea_orm::ColumnType::Float | sea_orm::ColumnType::Double => {}
-
-                _ => Err(anyhow::anyhow!("Unsupport column type"))?,
+                sea_orm::ColumnType::Json
+                | sea_orm::ColumnType::JsonBinary
+                | sea_orm::ColumnType::Array(_) => Err(anyhow::anyhow!("Unsupport column type"))?,
+                _ => {
+                    let value = coerce_filter_value(col_name, col_def, &json_force_str(value))?;
+                    cond = cond.add(Expr::col(col).eq(value));
+                }
             }
         } else if check_exists {
             return Err(anyhow::anyhow!("key not found"));
@@ -189,9 +452,12 @@ pub fn create_cond_from_input_json(
     Ok(cond)
 }
 
+/// Rows containing every query in one of the text columns, or equal to it in a number column.
+/// Postgres compares with `ILIKE`, the `LIKE` of SQLite and MySQL is already case-insensitive.
 pub fn create_cond_from_search_queries(
-    columns: &Vec<(String, ColumnDef)>,
-    queries: &Vec<String>,
+    columns: &[(String, ColumnDef)],
+    queries: &[String],
+    backend: DbBackend,
 ) -> Result<Condition> {
     let mut cond = Condition::any();
     for (col_name, col_def) in columns {
@@ -203,8 +469,21 @@ pub fn create_cond_from_search_queries(
             | sea_orm::ColumnType::Uuid => {
                 let mut pcond = Condition::all();
                 for value in queries {
-                    pcond =
-                        pcond.add(Expr::col(col.clone()).like(format!("%{}%", value.to_string())));
+                    let pattern = format!("%{}%", value);
+                    pcond = pcond.add(match backend {
+                        DbBackend::Postgres => {
+                            let sql = if matches!(col_def.get_column_type(), ColumnType::Uuid) {
+                                "CAST($1 AS TEXT) ILIKE $2"
+                            } else {
+                                "$1 ILIKE $2"
+                            };
+                            Expr::cust_with_exprs(
+                                sql,
+                                [Expr::col(col.clone()).into(), pattern.into()],
+                            )
+                        }
+                        _ => Expr::col(col.clone()).like(pattern),
+                    });
                 }
                 if !pcond.is_empty() {
                     cond = cond.add(pcond);
@@ -237,3 +516,169 @@ pub fn create_cond_from_search_queries(
 
     Ok(cond)
 }
+
+fn is_text_column(col_def: &ColumnDef) -> bool {
+    matches!(
+        col_def.get_column_type(),
+        ColumnType::Char(_) | ColumnType::String(_) | ColumnType::Text
+    )
+}
+
+/// Postgres full-text search of `queries` over the text columns, or-ed with the number columns
+/// equal to a query, and the `ts_rank` of the rows. `None` when there is nothing to search.
+pub fn create_cond_from_full_text_search(
+    columns: &[(String, ColumnDef)],
+    queries: &[String],
+) -> Result<(Condition, Option<SimpleExpr>)> {
+    let (text, others): (Vec<_>, Vec<_>) =
+        columns.iter().cloned().partition(|x| is_text_column(&x.1));
+    if queries.is_empty() || text.is_empty() {
+        return Ok((
+            create_cond_from_search_queries(&others, queries, DbBackend::Postgres)?,
+            None,
+        ));
+    }
+
+    let document = text
+        .iter()
+        .map(|(name, _)| format!("\"{}\"", name.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let document = format!("to_tsvector(concat_ws(' ', {}))", document);
+    let words = queries.join(" ");
+    let matches = Expr::cust_with_values(
+        format!("{} @@ plainto_tsquery($1)", document),
+        [words.clone()],
+    );
+    let rank = Expr::cust_with_values(
+        format!("ts_rank({}, plainto_tsquery($1))", document),
+        [words],
+    );
+
+    let cond = Condition::any().add(matches);
+    let c = create_cond_from_search_queries(&others, queries, DbBackend::Postgres)?;
+    let cond = if c.is_empty() { cond } else { cond.add(c) };
+    Ok((cond, Some(rank)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::sea_query::{PostgresQueryBuilder, Query};
+    use sea_orm::{prelude::StringLen, ColumnTypeTrait};
+
+    fn to_sql(cond: Condition) -> String {
+        Query::select()
+            .column(Alias::new("id"))
+            .from(Alias::new("person"))
+            .cond_where(cond)
+            .to_string(PostgresQueryBuilder)
+    }
+
+    fn columns() -> Vec<(String, ColumnDef)> {
+        vec![
+            ("id".into(), ColumnType::Integer.def()),
+            ("name".into(), ColumnType::String(StringLen::None).def()),
+            ("age".into(), ColumnType::Integer.def().null()),
+            ("active".into(), ColumnType::Boolean.def()),
+            ("created".into(), ColumnType::Date.def()),
+        ]
+    }
+
+    fn filter(pairs: &[(&str, &str)]) -> HashMap<String, Vec<String>> {
+        let mut m: HashMap<String, Vec<String>> = HashMap::new();
+        for (k, v) in pairs {
+            m.entry(String::from(*k))
+                .or_default()
+                .push(String::from(*v));
+        }
+        m
+    }
+
+    #[test]
+    fn test_split_lookup() {
+        assert_eq!(split_lookup("age__gte"), ("age", Lookup::Gte));
+        assert_eq!(split_lookup("name"), ("name", Lookup::Exact));
+        assert_eq!(split_lookup("first__name"), ("first__name", Lookup::Exact));
+    }
+
+    #[test]
+    fn test_create_cond_from_hash_map() {
+        let sql = |pairs: &[(&str, &str)]| {
+            to_sql(create_cond_from_hash_map(&columns(), &filter(pairs)).unwrap())
+        };
+        assert_eq!(
+            sql(&[("age__gte", "18"), ("age__lt", "65")]),
+            r#"SELECT "id" FROM "person" WHERE "age" >= 18 AND "age" < 65"#
+        );
+        assert_eq!(
+            sql(&[("name__icontains", "Fo%")]),
+            r#"SELECT "id" FROM "person" WHERE LOWER("name") LIKE E'%fo\\%%' ESCAPE E'\\'"#
+        );
+        assert_eq!(
+            sql(&[("id__in", "1,2,3")]),
+            r#"SELECT "id" FROM "person" WHERE "id" IN (1, 2, 3)"#
+        );
+        assert_eq!(
+            sql(&[("age__isnull", "true"), ("active", "false")]),
+            r#"SELECT "id" FROM "person" WHERE "age" IS NULL AND "active" = FALSE"#
+        );
+        assert_eq!(
+            sql(&[("created__range", "2023-01-01,2023-12-31")]),
+            r#"SELECT "id" FROM "person" WHERE "created" BETWEEN '2023-01-01' AND '2023-12-31'"#
+        );
+        assert_eq!(
+            sql(&[("name", "a"), ("name", "b"), ("unknown__gt", "1")]),
+            r#"SELECT "id" FROM "person" WHERE "name" = 'a' OR "name" = 'b'"#
+        );
+
+        let invalid =
+            |pairs: &[(&str, &str)]| create_cond_from_hash_map(&columns(), &filter(pairs)).is_err();
+        assert!(invalid(&[("age__gt", "abc")]));
+        assert!(invalid(&[("age__isnull", "maybe")]));
+        assert!(invalid(&[("age__range", "1")]));
+
+        let error =
+            create_cond_from_hash_map(&columns(), &filter(&[("age__contains", "1")])).unwrap_err();
+        let (status, _, fields) = AdminError::classify(&error).unwrap();
+        assert_eq!(status, 400);
+        assert!(fields.contains_key("age"));
+    }
+
+    #[test]
+    fn test_search_queries() {
+        let queries = vec!["Ann".to_string()];
+        let cond = create_cond_from_search_queries(&columns(), &queries, DbBackend::Postgres);
+        assert_eq!(
+            to_sql(cond.unwrap()),
+            r#"SELECT "id" FROM "person" WHERE "name" ILIKE '%Ann%'"#
+        );
+        let cond = create_cond_from_search_queries(&columns(), &queries, DbBackend::Sqlite);
+        assert_eq!(
+            to_sql(cond.unwrap()),
+            r#"SELECT "id" FROM "person" WHERE "name" LIKE '%Ann%'"#
+        );
+    }
+
+    #[test]
+    fn test_full_text_search() {
+        let queries = vec!["apple".to_string(), "pie".to_string()];
+        let (cond, rank) = create_cond_from_full_text_search(&columns(), &queries).unwrap();
+        assert_eq!(
+            to_sql(cond),
+            r#"SELECT "id" FROM "person" WHERE to_tsvector(concat_ws(' ', "name")) @@ plainto_tsquery('apple pie')"#
+        );
+        assert!(rank.is_some());
+
+        let queries = vec!["42".to_string()];
+        let (cond, _) = create_cond_from_full_text_search(&columns(), &queries).unwrap();
+        assert_eq!(
+            to_sql(cond),
+            r#"SELECT "id" FROM "person" WHERE (to_tsvector(concat_ws(' ', "name")) @@ plainto_tsquery('42')) OR ("id" = 42 OR "age" = 42)"#
+        );
+
+        let (cond, rank) = create_cond_from_full_text_search(&columns(), &[]).unwrap();
+        assert!(cond.is_empty());
+        assert!(rank.is_none());
+    }
+}
@@ -0,0 +1,850 @@
+//! Framework independent request handling, see `Admin::handle`.
+//! The web framework integrations (`axum_admin`, `actix_admin`) only convert
+//! their requests into an `AdminRequest` and the `AdminResponse` back.
+use crate::{
+    create_cond_from_key, json_overwrite_key, parse_cookie_header, verify_csrf_token, Admin,
+    AdminError, AdminUser, AuthRequest, CsrfToken, Json, ModelAdminTrait, Result, CSRF_COOKIE_NAME,
+    CSRF_HEADER_NAME,
+};
+use askama::Template;
+use futures_util::Stream;
+use log::error;
+use serde::Deserialize;
+use std::{collections::HashMap, pin::Pin, sync::Arc};
+
+/// Body of an `AdminResponse`, the integrations set the content type from the variant.
+pub enum AdminBody {
+    Empty,
+    Html(String),
+    Json(Json),
+    /// `text/csv` chunks, sent as they are produced.
+    Stream(Pin<Box<dyn Stream<Item = Result<String>> + Send>>),
+}
+
+impl std::fmt::Debug for AdminBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdminBody::Empty => write!(f, "Empty"),
+            AdminBody::Html(html) => f.debug_tuple("Html").field(html).finish(),
+            AdminBody::Json(value) => f.debug_tuple("Json").field(value).finish(),
+            AdminBody::Stream(_) => write!(f, "Stream"),
+        }
+    }
+}
+
+/// A request to the admin, independent of the web framework.
+#[derive(Debug, Clone, Default)]
+pub struct AdminRequest {
+    pub method: String,
+    /// Full path including `Admin::sub_path`, e.g. `/admin/cake/update/1/`.
+    pub path: String,
+    pub query: Option<String>,
+    /// Header names are lower case.
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl AdminRequest {
+    pub fn new(method: &str, path: &str) -> Self {
+        AdminRequest {
+            method: method.to_uppercase(),
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn query(mut self, query: Option<&str>) -> Self {
+        self.query = query.map(|x| x.to_string());
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_lowercase(), value.into());
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_lowercase()).map(|x| x.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct AdminResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: AdminBody,
+}
+
+impl AdminResponse {
+    pub fn status(status: u16) -> Self {
+        AdminResponse {
+            status,
+            headers: Vec::new(),
+            body: AdminBody::Empty,
+        }
+    }
+
+    pub fn html(html: String) -> Self {
+        AdminResponse {
+            body: AdminBody::Html(html),
+            ..Self::status(200)
+        }
+    }
+
+    pub fn json(status: u16, value: Json) -> Self {
+        AdminResponse {
+            body: AdminBody::Json(value),
+            ..Self::status(status)
+        }
+    }
+
+    /// `303 See Other` to `url`.
+    pub fn redirect(url: String) -> Self {
+        Self::status(303).header("location", &url)
+    }
+
+    /// `{"status": "failed", "error": ..}`
+    pub fn failed(status: u16, error: &str) -> Self {
+        Self::json(
+            status,
+            serde_json::json!({
+                "status": "failed",
+                "error": error,
+            }),
+        )
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_lowercase(), value.into()));
+        self
+    }
+
+    /// Json body of the response, for tests and non-html clients.
+    pub fn get_json(&self) -> Option<&Json> {
+        match &self.body {
+            AdminBody::Json(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Both variants are sent as they are, `Err` only allows `?` on failures.
+type AdminResult = std::result::Result<AdminResponse, AdminResponse>;
+
+/// Limit of request bodies, the integrations reject larger ones with 413.
+pub const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+/// Failure response of `error`, the status comes from `AdminError::classify`
+/// or is `status` for errors it does not know.
+/// `fields` of the envelope holds the messages per field of validation errors.
+fn log_error(status: u16) -> impl FnOnce(anyhow::Error) -> AdminResponse {
+    move |error| {
+        let (status, message, fields) = AdminError::classify(&error)
+            .unwrap_or_else(|| (status, error.to_string(), Default::default()));
+        if status >= 500 {
+            error!("Error: {error:?}");
+        }
+        let mut response = AdminResponse::failed(status, &message);
+        if let AdminBody::Json(body) = &mut response.body {
+            body["fields"] = serde_json::json!(fields);
+        }
+        response
+    }
+}
+
+impl From<AdminError> for AdminResponse {
+    fn from(error: AdminError) -> Self {
+        log_error(500)(error.into())
+    }
+}
+
+fn ensure_permission(allowed: bool) -> std::result::Result<(), AdminResponse> {
+    if allowed {
+        Ok(())
+    } else {
+        Err(AdminError::PermissionDenied.into())
+    }
+}
+
+fn get_model<'a>(
+    admin: &'a Admin,
+    model: &str,
+) -> std::result::Result<&'a Box<dyn ModelAdminTrait + Send + Sync>, AdminResponse> {
+    admin
+        .get_model(model)
+        .ok_or_else(|| AdminError::NotFound(format!("model {}", model)).into())
+}
+
+fn render<T: Template>(template: T) -> AdminResult {
+    Ok(AdminResponse::html(
+        template.render().map_err(|x| log_error(500)(x.into()))?,
+    ))
+}
+
+// ----- return json -----
+fn return_json_object(
+    model: &Box<dyn ModelAdminTrait + Send + Sync>,
+    r: Result<Json>,
+) -> AdminResponse {
+    let object = r.and_then(|data| {
+        Ok(serde_json::json!({
+            "status": "ok",
+            "key": Json::String(model.json_to_key(&data)?),
+            "label": Json::String(model.to_str(&data)?),
+            "data": data,
+        }))
+    });
+    match object {
+        Ok(object) => AdminResponse::json(200, object),
+        Err(error) => log_error(500)(error),
+    }
+}
+
+fn return_json<T>(r: Result<T>) -> AdminResponse {
+    match r {
+        Ok(_) => AdminResponse::json(200, serde_json::json!({ "status": "ok" })),
+        Err(error) => log_error(500)(error),
+    }
+}
+
+// ----- request parts -----
+fn parse_query_string(query: Option<&str>) -> HashMap<String, Vec<String>> {
+    let mut params: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, value) in form_urlencoded::parse(query.unwrap_or_default().as_bytes()) {
+        params
+            .entry(key.into_owned())
+            .or_default()
+            .push(value.into_owned());
+    }
+    params
+}
+
+/// Content negotiation of the list route, `Accept: application/json` selects the json api.
+fn accepts_json(accept: Option<&str>) -> bool {
+    accept.map(|x| x.contains("json")).unwrap_or(false)
+}
+
+/// Whether an unauthenticated request should get a 401 instead of the login redirect.
+fn wants_json(method: &str, accept: Option<&str>, content_type: Option<&str>) -> bool {
+    let is_json = |x: Option<&str>| x.map(|x| x.contains("json")).unwrap_or(false);
+    method != "GET" || is_json(accept) || is_json(content_type)
+}
+
+// ----- authentication -----
+fn login_redirect(login_url: &str, next: &str) -> AdminResponse {
+    let next: String = form_urlencoded::byte_serialize(next.as_bytes()).collect();
+    let separator = if login_url.contains('?') { '&' } else { '?' };
+    AdminResponse::redirect(format!("{}{}next={}", login_url, separator, next))
+}
+
+/// Resolves the user, or the login redirect / 401 to send instead.
+/// `next` is the path and query of the original request. The flag tells whether the
+/// `Authorization` header alone authenticated the user, only those requests skip the csrf check.
+async fn require_user(
+    admin: &Admin,
+    cookie: Option<&str>,
+    authorization: Option<&str>,
+    wants_json: bool,
+    next: &str,
+) -> std::result::Result<(AdminUser, bool), AdminResponse> {
+    if admin.has_auth() && authorization.is_some() {
+        let auth_request = AuthRequest::from_headers(None, authorization);
+        if let Some(user) = admin
+            .authenticate(&auth_request)
+            .await
+            .map_err(log_error(500))?
+        {
+            return Ok((user, true));
+        }
+    }
+    let auth_request = AuthRequest::from_headers(cookie, authorization);
+    if let Some(user) = admin
+        .authenticate(&auth_request)
+        .await
+        .map_err(log_error(500))?
+    {
+        return Ok((user, false));
+    }
+    match admin.login_url() {
+        Some(login_url) if !wants_json => Err(login_redirect(&login_url, next)),
+        _ => Err(AdminResponse::failed(401, "authentication required")),
+    }
+}
+
+// ----- csrf -----
+struct CsrfCheck {
+    token: CsrfToken,
+    /// The cookie has to be set on the response, see `csrf_cookie`.
+    is_new: bool,
+}
+
+/// Double-submit check of unsafe methods, the token of the cookie must be sent in `X-CSRF-Token`.
+fn check_csrf(
+    method: &str,
+    cookie: Option<&str>,
+    header_token: Option<&str>,
+    header_authenticated: bool,
+) -> std::result::Result<CsrfCheck, AdminResponse> {
+    let cookie_token = cookie
+        .map(parse_cookie_header)
+        .and_then(|mut x| x.remove(CSRF_COOKIE_NAME))
+        .filter(|x| !x.is_empty());
+
+    // browsers never attach an authorization header on their own, so clients authenticated
+    // by it are exempt, cookie sessions are checked even when the header is sent as well
+    let is_safe = matches!(method, "GET" | "HEAD" | "OPTIONS");
+    if !is_safe
+        && !header_authenticated
+        && !verify_csrf_token(cookie_token.as_deref(), header_token)
+    {
+        return Err(AdminResponse::failed(
+            403,
+            "csrf token missing or incorrect",
+        ));
+    }
+
+    Ok(CsrfCheck {
+        is_new: cookie_token.is_none(),
+        token: cookie_token
+            .map(CsrfToken)
+            .unwrap_or_else(CsrfToken::generate),
+    })
+}
+
+/// `Set-Cookie` value for a new csrf token.
+fn csrf_cookie(admin: &Admin, token: &CsrfToken) -> String {
+    format!(
+        "{}={}; Path={}/; HttpOnly; SameSite=Strict",
+        CSRF_COOKIE_NAME,
+        token.0,
+        admin.sub_path()
+    )
+}
+
+// ----- routes -----
+async fn index(admin: &Admin, user: &AdminUser) -> AdminResult {
+    render(crate::templates::AdminIndex::new(&admin.get_site(user)).map_err(log_error(500))?)
+}
+
+async fn list(
+    admin: &Admin,
+    model: &str,
+    user: &AdminUser,
+    csrf_token: &CsrfToken,
+    accepts_json: bool,
+    query: &HashMap<String, Vec<String>>,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_view_permission(user))?;
+    if accepts_json {
+        let object_list = admin
+            .get_list_as_json(model, query)
+            .await
+            .map_err(log_error(500))?;
+        Ok(AdminResponse::json(200, object_list))
+    } else {
+        render(
+            admin
+                .get_list_template(model, query, user, &csrf_token.0)
+                .await
+                .map_err(log_error(500))?,
+        )
+    }
+}
+
+fn export_csv(
+    admin: Arc<Admin>,
+    model: &str,
+    user: &AdminUser,
+    query: &HashMap<String, Vec<String>>,
+) -> AdminResult {
+    ensure_permission(get_model(&admin, model)?.has_view_permission(user))?;
+    let stream = crate::export_csv(admin.clone(), model, query).map_err(log_error(400))?;
+    Ok(AdminResponse {
+        body: AdminBody::Stream(Box::pin(stream)),
+        ..AdminResponse::status(200)
+    }
+    .header("content-type", "text/csv; charset=utf-8")
+    .header(
+        "content-disposition",
+        &format!("attachment; filename=\"{}.csv\"", model),
+    ))
+}
+
+async fn get_create_template(
+    admin: &Admin,
+    model: &str,
+    user: &AdminUser,
+    csrf_token: &CsrfToken,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_add_permission(user))?;
+    render(
+        admin
+            .get_create_template(model, user, &csrf_token.0)
+            .await
+            .map_err(log_error(500))?,
+    )
+}
+
+async fn create(admin: &Admin, model: &str, user: &AdminUser, data: &Json) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_add_permission(user))?;
+    Ok(return_json_object(
+        model,
+        admin.create(model, data, user, None).await,
+    ))
+}
+
+async fn get_row(
+    admin: &Admin,
+    model: &Box<dyn ModelAdminTrait + Send + Sync>,
+    id: &str,
+) -> std::result::Result<Json, AdminResponse> {
+    let key = model.key_to_json(id).map_err(log_error(400))?;
+    let cond = create_cond_from_key(model.as_ref(), &key).map_err(log_error(400))?;
+    model
+        .get(admin.get_connection(), &cond)
+        .await
+        .map_err(log_error(500))?
+        .ok_or_else(|| AdminError::NotFound(format!("row {}", id)).into())
+}
+
+async fn get_update_template(
+    admin: &Admin,
+    model: &str,
+    id: &str,
+    user: &AdminUser,
+    csrf_token: &CsrfToken,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_view_permission(user))?;
+    let row = get_row(admin, model, id).await?;
+    render(
+        admin
+            .get_update_template(model, &row, user, &csrf_token.0)
+            .await
+            .map_err(log_error(500))?,
+    )
+}
+
+async fn get_detail_template(
+    admin: &Admin,
+    model: &str,
+    id: &str,
+    user: &AdminUser,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_view_permission(user))?;
+    let row = get_row(admin, model, id).await?;
+    render(
+        admin
+            .get_detail_template(model.as_ref(), &row, user)
+            .await
+            .map_err(log_error(500))?,
+    )
+}
+
+async fn update(
+    admin: &Admin,
+    model: &str,
+    id: &str,
+    user: &AdminUser,
+    data: &Json,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_change_permission(user))?;
+    let key = model.key_to_json(id).map_err(log_error(400))?;
+    let data = json_overwrite_key(data, &key).map_err(log_error(500))?;
+    Ok(return_json_object(
+        model,
+        admin.update(model, &data, user, None).await,
+    ))
+}
+
+async fn get_delete_template(
+    admin: &Admin,
+    model: &str,
+    id: &str,
+    user: &AdminUser,
+    csrf_token: &CsrfToken,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_delete_permission(user))?;
+    let row = get_row(admin, model, id).await?;
+    render(
+        admin
+            .get_delete_template(model, &row, user, &csrf_token.0)
+            .await
+            .map_err(log_error(500))?,
+    )
+}
+
+async fn delete(
+    admin: &Admin,
+    model: &str,
+    id: &str,
+    user: &AdminUser,
+    data: &Json,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_delete_permission(user))?;
+    let key = model.key_to_json(id).map_err(log_error(400))?;
+    let data = json_overwrite_key(data, &key).map_err(log_error(500))?;
+    Ok(return_json(admin.delete(model, &data, user, None).await))
+}
+
+async fn get_bulk_delete_template(
+    admin: &Admin,
+    model: &str,
+    user: &AdminUser,
+    csrf_token: &CsrfToken,
+    query: &HashMap<String, Vec<String>>,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_delete_permission(user))?;
+    render(
+        admin
+            .get_bulk_delete_template(model, query, user, &csrf_token.0)
+            .await
+            .map_err(log_error(400))?,
+    )
+}
+
+async fn bulk_delete(admin: &Admin, model: &str, user: &AdminUser, data: &Json) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_delete_permission(user))?;
+    // all rows matching the filters of the list page, sent back as `?..`
+    if let Some(query) = data
+        .get("query")
+        .and_then(|x| x.as_str())
+        .filter(|x| !x.is_empty())
+    {
+        let query = parse_query_string(Some(query.trim_start_matches('?')));
+        return Ok(return_json(
+            admin
+                .bulk_delete_matching(model.as_ref(), &query, None)
+                .await,
+        ));
+    }
+    let keys: Vec<String> = data
+        .get("keys")
+        .and_then(|x| x.as_str())
+        .ok_or_else(|| AdminResponse::from(AdminError::field("keys", "required")))?
+        .split(',')
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_string())
+        .collect();
+    Ok(return_json(
+        admin.bulk_delete(model, &keys, user, None).await,
+    ))
+}
+
+async fn run_action(
+    admin: &Admin,
+    model: &str,
+    user: &AdminUser,
+    query: &HashMap<String, Vec<String>>,
+    data: &Json,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_change_permission(user))?;
+    let name = data
+        .get("action")
+        .and_then(|x| x.as_str())
+        .ok_or_else(|| AdminResponse::from(AdminError::field("action", "required")))?;
+    let rows = admin
+        .get_bulk_rows(model, query)
+        .await
+        .map_err(log_error(400))?;
+
+    match admin.run_action(model, name, rows, None).await {
+        Ok(message) => Ok(AdminResponse::json(
+            200,
+            serde_json::json!({
+                "status": "ok",
+                "message": message,
+            }),
+        )),
+        Err(error) => Ok(return_json::<()>(Err(error))),
+    }
+}
+
+async fn get_import_template(
+    admin: &Admin,
+    model: &str,
+    user: &AdminUser,
+    csrf_token: &CsrfToken,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_add_permission(user) || model.has_change_permission(user))?;
+    render(admin.get_import_template(model, user, &csrf_token.0))
+}
+
+#[derive(Deserialize)]
+struct AdminImportForm {
+    format: String,
+    data: String,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+async fn import(admin: &Admin, model: &str, user: &AdminUser, data: &Json) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_add_permission(user) || model.has_change_permission(user))?;
+    let rows = serde_json::from_value::<AdminImportForm>(data.clone())
+        .map_err(anyhow::Error::from)
+        .and_then(|form| {
+            let rows = crate::parse_import(form.format.parse()?, &form.data)?;
+            Ok((rows, form.dry_run))
+        });
+    let (rows, dry_run) = match rows {
+        Ok(rows) => rows,
+        Err(error) => return Ok(AdminResponse::failed(400, &error.to_string())),
+    };
+    match admin.import(model, rows, user, dry_run).await {
+        Ok(result) => Ok(AdminResponse::json(
+            200,
+            serde_json::json!({
+                "status": "ok",
+                "result": result,
+            }),
+        )),
+        Err(error) => Ok(return_json::<()>(Err(error))),
+    }
+}
+
+// ----- rest api -----
+/// `{key, label, data}` of a row, the shape of the api responses.
+fn api_object(model: &(dyn ModelAdminTrait + Send + Sync), status: u16, data: Json) -> AdminResult {
+    Ok(AdminResponse::json(
+        status,
+        serde_json::json!({
+            "key": model.json_to_key(&data).map_err(log_error(500))?,
+            "label": model.to_str(&data).map_err(log_error(500))?,
+            "data": data,
+        }),
+    ))
+}
+
+fn openapi(admin: &Admin, user: &AdminUser) -> AdminResult {
+    Ok(AdminResponse::json(
+        200,
+        crate::openapi_document(admin, user),
+    ))
+}
+
+async fn api_list(
+    admin: &Admin,
+    model: &str,
+    user: &AdminUser,
+    query: &HashMap<String, Vec<String>>,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_view_permission(user))?;
+    let object_list = admin
+        .get_list_as_json(model, query)
+        .await
+        .map_err(log_error(400))?;
+    Ok(AdminResponse::json(200, object_list))
+}
+
+async fn api_create(admin: &Admin, model: &str, user: &AdminUser, data: &Json) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_add_permission(user))?;
+    let row = admin
+        .create(model, data, user, None)
+        .await
+        .map_err(log_error(500))?;
+    api_object(model.as_ref(), 201, row)
+}
+
+async fn api_get(admin: &Admin, model: &str, id: &str, user: &AdminUser) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_view_permission(user))?;
+    let row = get_row(admin, model, id).await?;
+    api_object(model.as_ref(), 200, row)
+}
+
+/// `PUT` replaces the row, so every column but the primary keys must be given,
+/// `PATCH` only writes the given columns.
+async fn api_update(
+    admin: &Admin,
+    model: &str,
+    id: &str,
+    user: &AdminUser,
+    data: &Json,
+    replace: bool,
+) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_change_permission(user))?;
+    if !data.is_object() {
+        return Err(AdminResponse::failed(400, "expected an object"));
+    }
+    if replace {
+        let primary_keys = model.get_primary_keys();
+        let readonly_fields = model.get_readonly_fields();
+        let missing: Vec<String> = model
+            .get_columns()
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|x| {
+                !primary_keys.contains(x) && !readonly_fields.contains(x) && data.get(x).is_none()
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(AdminResponse::failed(
+                400,
+                &format!("missing columns: {}", missing.join(", ")),
+            ));
+        }
+    }
+    get_row(admin, model, id).await?;
+    let key = model.key_to_json(id).map_err(log_error(400))?;
+    let data = json_overwrite_key(data, &key).map_err(log_error(500))?;
+    let row = admin
+        .update(model, &data, user, None)
+        .await
+        .map_err(log_error(500))?;
+    api_object(model.as_ref(), 200, row)
+}
+
+async fn api_delete(admin: &Admin, model: &str, id: &str, user: &AdminUser) -> AdminResult {
+    let model = get_model(admin, model)?;
+    ensure_permission(model.has_delete_permission(user))?;
+    let row = get_row(admin, model, id).await?;
+    admin
+        .delete(model, &row, user, None)
+        .await
+        .map_err(log_error(500))?;
+    Ok(AdminResponse::status(204))
+}
+
+fn json_body(request: &AdminRequest) -> std::result::Result<Json, AdminResponse> {
+    serde_json::from_slice(&request.body)
+        .map_err(|_| AdminResponse::failed(400, "invalid json body"))
+}
+
+/// `path` is relative to `Admin::sub_path`.
+async fn route(
+    admin: Arc<Admin>,
+    request: &AdminRequest,
+    path: &str,
+    user: &AdminUser,
+    csrf_token: &CsrfToken,
+) -> AdminResult {
+    let query = parse_query_string(request.query.as_deref());
+    let segments: Vec<&str> = path.split('/').filter(|x| !x.is_empty()).collect();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", []) => index(&admin, user).await,
+        ("GET", ["api", "openapi.json"]) => openapi(&admin, user),
+        ("GET", ["api", model]) => api_list(&admin, model, user, &query).await,
+        ("POST", ["api", model]) => api_create(&admin, model, user, &json_body(request)?).await,
+        ("GET", ["api", model, id]) => api_get(&admin, model, id, user).await,
+        ("PUT", ["api", model, id]) => {
+            api_update(&admin, model, id, user, &json_body(request)?, true).await
+        }
+        ("PATCH", ["api", model, id]) => {
+            api_update(&admin, model, id, user, &json_body(request)?, false).await
+        }
+        ("DELETE", ["api", model, id]) => api_delete(&admin, model, id, user).await,
+        (_, ["api", "openapi.json"] | ["api", _] | ["api", _, _]) => {
+            Err(AdminResponse::status(405))
+        }
+        ("GET", [model]) => {
+            let accepts_json = accepts_json(request.get_header("accept"));
+            list(&admin, model, user, csrf_token, accepts_json, &query).await
+        }
+        ("GET", [model, "export"]) => export_csv(admin.clone(), model, user, &query),
+        ("GET", [model, "create"]) => get_create_template(&admin, model, user, csrf_token).await,
+        ("POST", [model, "create"]) => create(&admin, model, user, &json_body(request)?).await,
+        ("GET", [model, "view", id]) => get_detail_template(&admin, model, id, user).await,
+        ("GET", [model, "update", id]) => {
+            get_update_template(&admin, model, id, user, csrf_token).await
+        }
+        ("POST", [model, "update", id]) => {
+            update(&admin, model, id, user, &json_body(request)?).await
+        }
+        ("GET", [model, "delete", id]) => {
+            get_delete_template(&admin, model, id, user, csrf_token).await
+        }
+        ("POST", [model, "delete", id]) => {
+            delete(&admin, model, id, user, &json_body(request)?).await
+        }
+        ("GET", [model, "bulk-delete"]) => {
+            get_bulk_delete_template(&admin, model, user, csrf_token, &query).await
+        }
+        ("POST", [model, "bulk-delete"]) => {
+            bulk_delete(&admin, model, user, &json_body(request)?).await
+        }
+        ("POST", [model, "action"]) => {
+            run_action(&admin, model, user, &query, &json_body(request)?).await
+        }
+        ("GET", [model, "import"]) => get_import_template(&admin, model, user, csrf_token).await,
+        ("POST", [model, "import"]) => import(&admin, model, user, &json_body(request)?).await,
+        (
+            _,
+            []
+            | [_]
+            | [_, "export" | "create" | "bulk-delete" | "action" | "import"]
+            | [_, "view" | "update" | "delete", _],
+        ) => Err(AdminResponse::status(405)),
+        _ => Err(AdminResponse::status(404)),
+    }
+}
+
+impl Admin {
+    /// Authenticates the request, checks its csrf token and runs the matching route.
+    pub async fn handle(self: Arc<Self>, request: AdminRequest) -> AdminResponse {
+        let path = match request.path.strip_prefix(self.sub_path()) {
+            Some(path) if path.is_empty() || path.starts_with('/') => path.to_string(),
+            _ => return AdminResponse::status(404),
+        };
+
+        let next = match request.query.as_deref().filter(|x| !x.is_empty()) {
+            Some(query) => format!("{}?{}", request.path, query),
+            None => request.path.clone(),
+        };
+        let wants_json = wants_json(
+            &request.method,
+            request.get_header("accept"),
+            request.get_header("content-type"),
+        );
+        let (user, header_authenticated) = match require_user(
+            &self,
+            request.get_header("cookie"),
+            request.get_header("authorization"),
+            wants_json,
+            &next,
+        )
+        .await
+        {
+            Ok(user) => user,
+            Err(response) => return response,
+        };
+
+        let check = match check_csrf(
+            &request.method,
+            request.get_header("cookie"),
+            request.get_header(CSRF_HEADER_NAME),
+            header_authenticated,
+        ) {
+            Ok(check) => check,
+            Err(response) => return response,
+        };
+
+        let response = route(self.clone(), &request, &path, &user, &check.token)
+            .await
+            .unwrap_or_else(|x| x);
+        if check.is_new {
+            response.header("set-cookie", &csrf_cookie(&self, &check.token))
+        } else {
+            response
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{Json, Result};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+impl std::str::FromStr for ImportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ImportFormat::Csv),
+            "json" => Ok(ImportFormat::Json),
+            _ => Err(anyhow::anyhow!("unsupported import format {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportDecision {
+    Create,
+    Update,
+    /// The key of the row could not be looked up, it is neither created nor updated.
+    Error,
+}
+
+/// Outcome of one imported row, `line` is 1-based and counts data rows only.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportRow {
+    pub line: usize,
+    pub decision: ImportDecision,
+    pub key: Option<String>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportResult {
+    pub rows: Vec<ImportRow>,
+    /// Columns of the file that are not columns of the model.
+    pub ignored_columns: Vec<String>,
+    /// false for dry-runs and when any row failed, nothing is written then.
+    pub committed: bool,
+}
+
+impl ImportResult {
+    pub fn has_errors(&self) -> bool {
+        self.rows.iter().any(|x| !x.errors.is_empty())
+    }
+}
+
+/// Splits CSV text into records, quoted fields may contain commas, quotes (`""`) and newlines.
+fn parse_csv_records(text: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' | '\n' => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(anyhow::anyhow!("unterminated quoted field"));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records
+        .into_iter()
+        .filter(|x| !(x.len() == 1 && x[0].is_empty()))
+        .collect())
+}
+
+/// Rows of a CSV (header line first) or JSON (array of objects) file as json objects.
+pub fn parse_import(format: ImportFormat, text: &str) -> Result<Vec<Json>> {
+    match format {
+        ImportFormat::Csv => {
+            let mut records = parse_csv_records(text)?.into_iter();
+            let header = records.next().unwrap_or_default();
+            records
+                .enumerate()
+                .map(|(i, record)| {
+                    if record.len() != header.len() {
+                        return Err(anyhow::anyhow!(
+                            "line {}: expected {} fields, found {}",
+                            i + 1,
+                            header.len(),
+                            record.len()
+                        ));
+                    }
+                    Ok(Json::Object(
+                        header
+                            .iter()
+                            .cloned()
+                            .zip(record.into_iter().map(Json::String))
+                            .collect(),
+                    ))
+                })
+                .collect()
+        }
+        ImportFormat::Json => match serde_json::from_str(text)? {
+            Json::Array(rows) => {
+                if rows.iter().all(|x| x.is_object()) {
+                    Ok(rows)
+                } else {
+                    Err(anyhow::anyhow!("every row must be an object"))
+                }
+            }
+            _ => Err(anyhow::anyhow!("expected an array of objects")),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_import() {
+        let rows = parse_import(
+            ImportFormat::Csv,
+            "id,name\r\n1,\"cheese, \"\"blue\"\"\"\n,\"two\nlines\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                json!({"id": "1", "name": "cheese, \"blue\""}),
+                json!({"id": "", "name": "two\nlines"}),
+            ]
+        );
+        assert!(parse_import(ImportFormat::Csv, "id,name\n1\n").is_err());
+        assert!(parse_import(ImportFormat::Csv, "id\n\"1\n").is_err());
+
+        let rows = parse_import(ImportFormat::Json, r#"[{"id": 1, "name": "a"}]"#).unwrap();
+        assert_eq!(rows, vec![json!({"id": 1, "name": "a"})]);
+        assert!(parse_import(ImportFormat::Json, r#"{"id": 1}"#).is_err());
+        assert!(parse_import(ImportFormat::Json, "[1]").is_err());
+    }
+}
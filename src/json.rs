@@ -1,9 +1,9 @@
-use crate::{CustomError, Json, Result};
+use crate::{AdminError, CustomError, Json, Result};
 use base64::Engine;
 use log::warn;
 #[cfg(feature = "with-rust_decimal")]
 use rust_decimal::Decimal;
-use sea_orm::{ActiveModelTrait, EntityTrait, Iden, ModelTrait};
+use sea_orm::{ActiveModelTrait, DbBackend, EntityTrait, Iden, ModelTrait};
 
 // ----------------------------------------------------------------------------
 pub fn set_from_json<M>(
@@ -14,15 +14,47 @@ pub fn set_from_json<M>(
 where
     M: ActiveModelTrait,
 {
-    columns
-        .iter()
-        .map(|col| {
-            if let Some(v) = src.get(col.to_string()) {
-                target.set(*col, sanitize_value(col, v)?);
+    set_values_from_json(target, columns, src, None)
+}
+
+/// Same as `set_from_json`, also rejecting the values `backend` can not store.
+pub fn set_from_json_for_backend<M>(
+    target: &mut M,
+    columns: &Vec<<<M as ActiveModelTrait>::Entity as EntityTrait>::Column>,
+    src: &Json,
+    backend: DbBackend,
+) -> Result<()>
+where
+    M: ActiveModelTrait,
+{
+    set_values_from_json(target, columns, src, Some(backend))
+}
+
+fn set_values_from_json<M>(
+    target: &mut M,
+    columns: &Vec<<<M as ActiveModelTrait>::Entity as EntityTrait>::Column>,
+    src: &Json,
+    backend: Option<DbBackend>,
+) -> Result<()>
+where
+    M: ActiveModelTrait,
+{
+    let mut errors = std::collections::BTreeMap::new();
+    for col in columns.iter() {
+        if let Some(v) = src.get(col.to_string()) {
+            match sanitize_value(col, v, backend) {
+                Ok(value) => target.set(*col, value),
+                Err(error) => {
+                    errors.insert(col.to_string(), vec![error.to_string()]);
+                }
             }
-            Ok(())
-        })
-        .collect::<Result<()>>()
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AdminError::Validation(errors).into())
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -83,7 +115,7 @@ pub fn json_overwrite_key(
 // ----------------------------------------------------------------------------
 pub fn json_convert_vec_to_json(
     model: &Box<dyn super::ModelAdminTrait + Send + Sync>,
-    total: u64,
+    total: super::ListTotal,
     object_list: Vec<Json>,
 ) -> Result<Json> {
     let object_list = object_list
@@ -107,7 +139,8 @@ pub fn json_convert_vec_to_json(
         .collect::<Result<Vec<_>>>()?;
 
     Ok(serde_json::json!({
-        "total" : total,
+        "total" : total.value(),
+        "total_exact": total.is_exact(),
         "data": Json::Array(object_list),
     }))
 }
@@ -133,16 +166,23 @@ macro_rules! sanitize_value_check_empty {
             if $col.def().is_null() {
                 return Ok(sea_orm::Value::$ident(None));
             } else {
-                return Err(anyhow::anyhow!(format!(
-                    "{:?} cannot be null",
-                    $col.to_string()
-                )));
+                return Err(anyhow::anyhow!("cannot be null"));
             }
         }
     };
 }
 
-fn sanitize_value<C>(col: &C, v: &Json) -> Result<sea_orm::Value>
+/// Whether `v` holds the NUL character in a string or an object key.
+fn json_contains_nul(v: &Json) -> bool {
+    match v {
+        Json::String(x) => x.contains('\0'),
+        Json::Array(x) => x.iter().any(json_contains_nul),
+        Json::Object(x) => x.iter().any(|(k, v)| k.contains('\0') || json_contains_nul(v)),
+        _ => false,
+    }
+}
+
+fn sanitize_value<C>(col: &C, v: &Json, backend: Option<DbBackend>) -> Result<sea_orm::Value>
 where
     C: sea_orm::ColumnTrait,
 {
@@ -255,9 +295,17 @@ where
         // sea_orm::ColumnType::Money(o) => {}
         sea_orm::ColumnType::Json | sea_orm::ColumnType::JsonBinary => {
             sanitize_value_check_empty!(Json, col, v);
-            let v: String = serde_json::from_value(v.clone())?;
-            let v: Json = serde_json::from_str(&v)?;
-            sea_orm::Value::Json(Some(Box::new(v.clone())))
+            // forms post the json text, the api posts the value itself
+            let v: Json = match v {
+                Json::String(text) => serde_json::from_str(text)?,
+                v => v.clone(),
+            };
+            // postgres binds json values as jsonb, which can not hold the NUL character,
+            // sqlite stores the json text and mysql its json type as they are
+            if backend == Some(DbBackend::Postgres) && json_contains_nul(&v) {
+                return Err(anyhow::anyhow!("\\u0000 is not supported in json"));
+            }
+            sea_orm::Value::Json(Some(Box::new(v)))
         }
         #[cfg(feature = "with-uuid")]
         sea_orm::ColumnType::Uuid => {
@@ -385,7 +433,7 @@ mod tests {
         use sea_orm::entity::prelude::*;
 
         #[derive(Clone, Debug, PartialEq, EnumIter, DeriveActiveEnum)]
-        #[sea_orm(rs_type = "String", db_type = "String(None)")]
+        #[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
         pub enum Category {
             #[sea_orm(string_value = "B")]
             Big,
@@ -436,7 +484,7 @@ mod tests {
             pub timestamp_with_time_zone_f: Option<DateTimeWithTimeZone>,
             pub time_f: Option<Time>,
             pub date_f: Option<Date>,
-            #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
+            #[sea_orm(column_type = "Binary(16)", nullable)]
             pub binary_f: Option<Vec<u8>>,
 
             pub boolean_f: Option<bool>,
@@ -569,7 +617,7 @@ mod tests {
             ("timestamp_f", json!("2022-01-01T12:00:00.123456")),
             (
                 "timestamp_with_time_zone_f",
-                json!("2022-01-01T12:00:00.123456+00:00"),
+                json!("2022-01-01T12:00:00.123456Z"),
             ),
             ("tiny_integer_f", json!(1)),
             ("tiny_unsigned_f", json!(5)),
@@ -663,6 +711,23 @@ mod tests {
         assert_eq!(set_result.is_err(), true);
     }
 
+    #[test]
+    fn test_json_nul_for_backend() {
+        let fields = vec![test_model::Column::JsonF];
+        let jv = json!({"json_f": {"a": "b\u{0}"}});
+        for (backend, is_err) in [
+            (DbBackend::Postgres, true),
+            (DbBackend::Sqlite, false),
+            (DbBackend::MySql, false),
+        ] {
+            let mut a = test_model::ActiveModel {
+                ..Default::default()
+            };
+            let result = set_from_json_for_backend(&mut a, &fields, &jv, backend);
+            assert_eq!(result.is_err(), is_err, "{:?}", backend);
+        }
+    }
+
     // #[test]
     // fn test_json_convert_vec_to_json() {
     // なんでだめ？？
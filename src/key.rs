@@ -1,21 +1,33 @@
-use crate::{CustomError, Json, Result};
+use crate::{
+    create_cond_from_input_json, json_force_str, AdminError, CustomError, Json, ModelAdminTrait,
+    Result,
+};
+use sea_orm::Condition;
+
+/// Prefix of keys in the current encoding. Keys without it are decoded with the
+/// original encoding, which only supported string, integer and uuid columns.
+const KEY_VERSION: &str = "1.";
 
 // ----------------------------------------------------------------------------
 pub fn to_key_string<C>(columns: &Vec<C>, value: &Json) -> Result<String>
 where
     C: sea_orm::ColumnTrait,
 {
-    Ok(columns
-        .iter()
-        .map(|c| {
-            let v = value.get(c.to_string());
-            if v.is_none() {
-                return Err(anyhow::anyhow!("key not set"));
-            }
-            b62encode(c, v.unwrap())
-        })
-        .collect::<Result<Vec<_>>>()?
-        .join("-"))
+    Ok(format!(
+        "{}{}",
+        KEY_VERSION,
+        columns
+            .iter()
+            .map(|c| {
+                let v = value.get(c.to_string());
+                if v.is_none() {
+                    return Err(anyhow::anyhow!("key not set"));
+                }
+                encode_part(c, v.unwrap())
+            })
+            .collect::<Result<Vec<_>>>()?
+            .join("-")
+    ))
 }
 
 // ----------------------------------------------------------------------------
@@ -23,79 +35,101 @@ pub fn from_key_string<C>(columns: &Vec<C>, key: &str) -> Result<Json>
 where
     C: sea_orm::ColumnTrait,
 {
+    let (versioned, key_body) = match key.strip_prefix(KEY_VERSION) {
+        Some(body) => (true, body),
+        None => (false, key),
+    };
+    let parts: Vec<&str> = key_body.split("-").collect();
+    if parts.len() != columns.len() {
+        return Err(AdminError::NotFound(format!("invalid key {:?}", key)).into());
+    }
     let mut m = serde_json::Map::new();
-    for (v, col) in key.split("-").zip(columns).into_iter() {
-        m.insert(col.to_string(), b62decode(col, v)?);
+    for (v, col) in parts.into_iter().zip(columns) {
+        let value = if versioned {
+            decode_part(col, v)
+        } else {
+            b62decode(col, v)
+        }
+        .map_err(|e| AdminError::NotFound(format!("invalid key {:?}: {}", key, e)))?;
+        m.insert(col.to_string(), value);
     }
     Ok(Json::Object(m))
 }
 
-// ============================================================================
-// macro_rules! b62encode_tostring {
-//     ($i:expr) => {
-//         if let Some(i) = $i {
-//             i.to_string()
-//         } else {
-//             return Err(Box::new(CustomError::new("b62encode")));
-//         }
-//     };
-// }
-
-fn b62encode<C>(col: &C, value: &Json) -> Result<String>
+/// Condition selecting the row whose primary key values are in `key`,
+/// compared as values of the column types.
+pub fn create_cond_from_key(
+    model: &(dyn ModelAdminTrait + Send + Sync),
+    key: &Json,
+) -> Result<Condition> {
+    let primary_keys = model.get_primary_keys();
+    let columns: Vec<_> = model
+        .get_columns()
+        .into_iter()
+        .filter(|(name, _)| primary_keys.contains(name))
+        .collect();
+    create_cond_from_input_json(&columns, key, true)
+}
+
+// ----------------------------------------------------------------------------
+/// A part is the text of the value when it is only made of `[0-9A-Za-z_]`,
+/// otherwise `~` and the base62 of the text, so it never contains the `-` separator.
+fn encode_part<C>(col: &C, value: &Json) -> Result<String>
 where
     C: sea_orm::ColumnTrait,
 {
-    match col.def().get_column_type() {
-        sea_orm::ColumnType::Char(_)
-        | sea_orm::ColumnType::String(_)
-        | sea_orm::ColumnType::Text => value
-            .as_str()
-            .ok_or(anyhow::anyhow!("parse json error"))
-            .map(|x| base_62::encode(x.as_bytes())),
+    match (col.def().get_column_type(), value) {
+        (
+            sea_orm::ColumnType::Json
+            | sea_orm::ColumnType::JsonBinary
+            | sea_orm::ColumnType::Array(_),
+            _,
+        ) => Err(anyhow::anyhow!("Unsupported Column type for key_to_str")),
+        (_, Json::String(_) | Json::Number(_) | Json::Bool(_)) => {
+            let text = json_force_str(value);
+            if !text.is_empty() && text.chars().all(|x| x.is_ascii_alphanumeric() || x == '_') {
+                Ok(text)
+            } else {
+                Ok(format!("~{}", base_62::encode(text.as_bytes())))
+            }
+        }
+        _ => Err(anyhow::anyhow!("key must be string, number or bool")),
+    }
+}
+
+fn decode_part<C>(col: &C, v: &str) -> Result<Json>
+where
+    C: sea_orm::ColumnTrait,
+{
+    let text =
+        match v.strip_prefix('~') {
+            Some(encoded) => String::from_utf8(base_62::decode(encoded).map_err(|e| {
+                Box::new(CustomError::new(format!("base_62::decode error: {:?}", e)))
+            })?)?,
+            None => v.to_string(),
+        };
+    Ok(match col.def().get_column_type() {
         sea_orm::ColumnType::TinyInteger
         | sea_orm::ColumnType::SmallInteger
         | sea_orm::ColumnType::Integer
         | sea_orm::ColumnType::BigInteger
-        | sea_orm::ColumnType::TinyUnsigned
+        | sea_orm::ColumnType::Year => text.parse::<i64>()?.into(),
+        sea_orm::ColumnType::TinyUnsigned
         | sea_orm::ColumnType::SmallUnsigned
         | sea_orm::ColumnType::Unsigned
-        | sea_orm::ColumnType::BigUnsigned => value
-            .as_i64()
-            .ok_or(anyhow::anyhow!("parse json error"))
-            .map(|x| x.to_string()),
-
-        // sea_orm::ColumnType::Float => {}
-        // sea_orm::ColumnType::Double => {}
-        // sea_orm::ColumnType::Decimal(o) => {}
-        // sea_orm::ColumnType::DateTime => {}
-        // sea_orm::ColumnType::Timestamp => {}
-        // sea_orm::ColumnType::TimestampWithTimeZone => {}
-        // sea_orm::ColumnType::Time => {}
-        // sea_orm::ColumnType::Date => {}
-        // sea_orm::ColumnType::Year(o) => {}
-        // sea_orm::ColumnType::Interval(i, o) => {},
-        // sea_orm::ColumnType::Binary(o) => {}
-        // sea_orm::ColumnType::VarBinary(o) => {}
-        // sea_orm::ColumnType::Bit(o) => {}
-        // sea_orm::ColumnType::VarBit(o) => {}
-        // sea_orm::ColumnType::Boolean => {}
-        // sea_orm::ColumnType::Money(o) => {}
-        // sea_orm::ColumnType::Json => {}
-        // sea_orm::ColumnType::JsonBinary => {}
-        sea_orm::ColumnType::Uuid => {
-            let uuid: uuid::Uuid = serde_json::from_value(value.clone())?;
-            Ok(base_62::encode(uuid.as_bytes()))
-        }
-        // sea_orm::ColumnType::Custom(DynIden),
-        // sea_orm::ColumnType::Enum
-        // sea_orm::ColumnType::Array(SeaRc<ColumnType>),
-        // sea_orm::ColumnType::Cidr,
-        // sea_orm::ColumnType::Inet,
-        // sea_orm::ColumnType::MacAddr,
-        _ => Err(anyhow::anyhow!("Unsupported Column type for key_to_str",)),
-    }
+        | sea_orm::ColumnType::BigUnsigned => text.parse::<u64>()?.into(),
+        sea_orm::ColumnType::Float | sea_orm::ColumnType::Double => text.parse::<f64>()?.into(),
+        sea_orm::ColumnType::Boolean => text.parse::<bool>()?.into(),
+        sea_orm::ColumnType::Json
+        | sea_orm::ColumnType::JsonBinary
+        | sea_orm::ColumnType::Array(_) => Err(anyhow::anyhow!("b62decode"))?,
+        _ => Json::String(text),
+    })
 }
 
+// ============================================================================
+// original encoding, decoded for the links created before `KEY_VERSION`
+
 macro_rules! b62decode_parse {
     ($i:expr, $t: ty, $V: ident) => {
         serde_json::to_value($i.parse::<$t>().map_err(|x| Box::new(x))?).map_err(|x| x.into())
@@ -147,3 +181,82 @@ where
         _ => Err(anyhow::anyhow!("b62decode")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    mod keyed {
+        use sea_orm::entity::prelude::*;
+
+        #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+        #[sea_orm(table_name = "keyed")]
+        pub struct Model {
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub day: Date,
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub region: String,
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub code: Decimal,
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub active: bool,
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub n: i32,
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub id: Uuid,
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub stamp: DateTimeWithTimeZone,
+            #[sea_orm(primary_key, auto_increment = false, column_type = "Binary(16)")]
+            pub data: Vec<u8>,
+        }
+
+        #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+        pub enum Relation {}
+
+        impl ActiveModelBehavior for ActiveModel {}
+    }
+
+    #[test]
+    fn test_key_round_trip() {
+        use keyed::Column;
+        use sea_orm::Iterable;
+
+        let columns: Vec<Column> = Column::iter().collect();
+        let value = json!({
+            "day": "2024-02-29",
+            "region": "eu-west",
+            "code": "12.50",
+            "active": true,
+            "n": -3,
+            "id": "67e55044-10b1-426f-9247-bb680e5fe0c8",
+            "stamp": "2024-02-29T10:00:00+09:00",
+            "data": "AAEC",
+        });
+        let key = to_key_string(&columns, &value).unwrap();
+        assert!(key.starts_with(KEY_VERSION));
+        assert_eq!(key.matches('-').count(), columns.len() - 1);
+        assert_eq!(from_key_string(&columns, &key).unwrap(), value);
+
+        let key = to_key_string(
+            &vec![Column::N, Column::Region],
+            &json!({"n": 42, "region": "eu"}),
+        );
+        assert_eq!(key.unwrap(), "1.42-eu");
+        let value = json!({"region": ""});
+        let key = to_key_string(&vec![Column::Region], &value).unwrap();
+        assert_eq!(from_key_string(&vec![Column::Region], &key).unwrap(), value);
+
+        // links created before the versioned encoding
+        assert_eq!(
+            from_key_string(
+                &vec![Column::N, Column::Region],
+                &format!("42-{}", base_62::encode(b"eu"))
+            )
+            .unwrap(),
+            json!({"n": 42, "region": "eu"})
+        );
+        assert!(from_key_string(&vec![Column::N], "1.x").is_err());
+        assert!(from_key_string(&vec![Column::N], "1.1-2").is_err());
+    }
+}
@@ -3,26 +3,56 @@ use std::collections::HashMap;
 pub use async_trait::async_trait;
 pub use sea_orm;
 pub use sea_orm::Iden;
-use sea_orm::{ColumnDef, Condition, DatabaseConnection, DatabaseTransaction};
+use sea_orm::{ColumnDef, Condition, DatabaseConnection, DatabaseTransaction, RelationDef};
+extern crate self as seaorm_admin;
+
+mod action;
+#[cfg(feature = "with-actix")]
+pub mod actix_admin;
 mod admin;
+mod audit;
+mod auth;
 #[cfg(feature = "with-axum")]
 pub mod axum_admin;
+mod cascade;
+mod csrf;
+mod count;
+mod cursor;
 mod error;
+mod export;
+mod facet;
 mod field;
 mod filter;
+mod handler;
+mod import;
 mod json;
 mod key;
+mod openapi;
 mod parse;
 pub mod templates;
+mod validate;
 
+pub use action::*;
 pub use admin::*;
+pub use audit::*;
 pub use admin_macro::ModelAdmin;
+pub use auth::*;
+pub use cascade::*;
+pub use count::*;
+pub use csrf::*;
+pub use cursor::*;
 pub use error::*;
+pub use export::*;
+pub use facet::*;
 pub use field::*;
 pub use filter::*;
+pub use handler::*;
+pub use import::*;
 pub use json::*;
 pub use key::*;
+pub use openapi::*;
 pub use parse::*;
+pub use validate::*;
 
 pub type Result<T> = std::result::Result<T, anyhow::Error>;
 pub type Json = serde_json::Value;
@@ -34,6 +64,8 @@ pub struct ListQuery {
     pub ordering: Vec<(String, sea_orm::Order)>,
     pub offset: u64,
     pub limit: u64,
+    /// The `_c` parameter of cursor paginated lists.
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -42,6 +74,10 @@ pub struct ListParam {
     pub ordering: Vec<(String, sea_orm::Order)>,
     pub offset: Option<u64>,
     pub limit: Option<u64>,
+    /// Whether `list` also counts the rows matching `cond`, it returns 0 otherwise.
+    pub count: bool,
+    /// Relevance of a full-text search, rows are ordered by it before `ordering`.
+    pub rank: Option<sea_orm::sea_query::SimpleExpr>,
 }
 
 #[async_trait]
@@ -61,9 +97,178 @@ pub trait ModelAdminTrait {
 
     fn get_form_fields(&self) -> Vec<AdminField>;
 
+    /// Columns shown disabled on the update form and left unchanged by updates.
+    fn get_readonly_fields(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Groups of the create and update forms, empty renders the fields as one list.
+    fn get_fieldsets(&self) -> Vec<Fieldset> {
+        Vec::new()
+    }
+
+    /// Relations of the entity, the `belongs_to` ones are followed from the referenced rows on delete.
+    fn get_relations(&self) -> Vec<RelationDef> {
+        Vec::new()
+    }
+
+    fn has_view_permission(&self, user: &AdminUser) -> bool {
+        user.has_perm(self.get_table_name(), AdminPermission::View)
+            || user.has_perm(self.get_table_name(), AdminPermission::Change)
+    }
+    fn has_add_permission(&self, user: &AdminUser) -> bool {
+        user.has_perm(self.get_table_name(), AdminPermission::Add)
+    }
+    fn has_change_permission(&self, user: &AdminUser) -> bool {
+        user.has_perm(self.get_table_name(), AdminPermission::Change)
+    }
+    fn has_delete_permission(&self, user: &AdminUser) -> bool {
+        user.has_perm(self.get_table_name(), AdminPermission::Delete)
+    }
+
+    /// Actions offered for the selected rows, running one requires change permission.
+    fn get_actions(&self) -> Vec<AdminAction> {
+        Vec::new()
+    }
+
+    /// Ordering of the list when the query string has no `_o`.
+    fn default_ordering(&self) -> Vec<(String, sea_orm::Order)> {
+        self.get_primary_keys()
+            .into_iter()
+            .map(|x| (x, sea_orm::Order::Desc))
+            .collect()
+    }
+
+    fn get_pagination(&self) -> Pagination {
+        Pagination::Offset
+    }
+
+    /// `Exact` for offset pagination, cursor pagination skips the count by default.
+    fn get_count_strategy(&self) -> CountStrategy {
+        match self.get_pagination() {
+            Pagination::Offset => CountStrategy::Exact,
+            Pagination::Cursor => CountStrategy::None,
+        }
+    }
+
+    /// Planner estimate of the rows matching `cond` for `CountStrategy::Estimated`,
+    /// `None` falls back to an exact count.
+    async fn estimate_count(
+        &self,
+        _conn: &DatabaseConnection,
+        _cond: &Condition,
+    ) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Columns matched by the `_q` search, every column by default.
+    fn get_search_fields(&self) -> Vec<String> {
+        self.get_columns().into_iter().map(|x| x.0).collect()
+    }
+
+    fn get_search_mode(&self) -> SearchMode {
+        SearchMode::Contains
+    }
+
+    /// Columns shown as facets next to the list.
+    fn list_filter(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Runs inside the transaction of `Admin::create` and `Admin::update` before the row is written,
+    /// the returned value is written instead of `value`. An error rolls the transaction back.
+    async fn before_save(
+        &self,
+        _conn: &DatabaseTransaction,
+        value: Json,
+        _user: &AdminUser,
+        _operation: SaveOperation,
+    ) -> Result<Json> {
+        Ok(value)
+    }
+    /// Runs inside the same transaction with the saved row.
+    async fn after_save(
+        &self,
+        _conn: &DatabaseTransaction,
+        _row: &Json,
+        _user: &AdminUser,
+        _operation: SaveOperation,
+    ) -> Result<()> {
+        Ok(())
+    }
+    /// Runs inside the transaction of `Admin::delete`, `key` holds at least the primary key values of the row.
+    async fn before_delete(
+        &self,
+        _conn: &DatabaseTransaction,
+        _key: &Json,
+        _user: &AdminUser,
+    ) -> Result<()> {
+        Ok(())
+    }
+    /// Runs inside the same transaction once the row is deleted.
+    async fn after_delete(
+        &self,
+        _conn: &DatabaseTransaction,
+        _key: &Json,
+        _user: &AdminUser,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     async fn list(&self, conn: &DatabaseConnection, param: &ListParam) -> Result<(u64, Vec<Json>)>;
+    /// Number of rows matching `cond`, the default counts through `list`.
+    async fn count(&self, conn: &DatabaseConnection, cond: &Condition) -> Result<u64> {
+        let param = ListParam {
+            cond: cond.clone(),
+            ordering: vec![],
+            offset: None,
+            limit: Some(0),
+            count: true,
+            rank: None,
+        };
+        Ok(self.list(conn, &param).await?.0)
+    }
+    /// Number of rows for each distinct value of `column`, the `limit` most frequent values first.
+    /// Used by `list_filter`, the default returns an error.
+    async fn count_by(
+        &self,
+        _conn: &DatabaseConnection,
+        _cond: &Condition,
+        _column: &str,
+        _limit: u64,
+    ) -> Result<Vec<(Json, u64)>> {
+        Err(CustomError::new(format!(
+            "count_by is not implemented for {}",
+            self.get_table_name()
+        ))
+        .into())
+    }
     async fn get(&self, conn: &DatabaseConnection, cond: &Condition) -> Result<Option<Json>>;
+    /// Converts `value` with `set_from_json` without saving it, to report invalid values early.
+    /// The default accepts every value, they are checked when saved then.
+    fn sanitize_json(&self, _value: &Json) -> Result<()> {
+        Ok(())
+    }
+    /// Same as `get`, but reads inside `conn` so it sees the uncommitted state of the transaction.
+    /// Used by the audit log and the import, the default returns `None`: no snapshot is recorded
+    /// and every imported row is inserted.
+    async fn get_with_txn(
+        &self,
+        _conn: &DatabaseTransaction,
+        _cond: &Condition,
+    ) -> Result<Option<Json>> {
+        Ok(None)
+    }
     async fn insert(&self, conn: &DatabaseTransaction, value: &Json) -> Result<Json>;
     async fn update(&self, conn: &DatabaseTransaction, value: &Json) -> Result<Json>;
     async fn delete(&self, conn: &DatabaseTransaction, cond: &Condition) -> Result<u64>;
+    /// Deletes every row matching `cond` with one statement, `delete` removes a single row.
+    /// Used by the bulk delete of all matching rows, the default returns an error.
+    async fn delete_many(&self, _conn: &DatabaseTransaction, _cond: &Condition) -> Result<u64> {
+        Err(CustomError::new(format!(
+            "delete_many is not implemented for {}",
+            self.get_table_name()
+        ))
+        .into())
+    }
 }
@@ -0,0 +1,280 @@
+use crate::{Admin, AdminUser, Json, ModelAdminTrait};
+use sea_orm::{ColumnDef, ColumnType};
+use serde_json::json;
+
+/// JSON schema of a column value as written by `to_json`.
+fn column_schema(def: &ColumnDef) -> Json {
+    let mut schema = match def.get_column_type() {
+        ColumnType::Char(_) | ColumnType::String(_) | ColumnType::Text => {
+            json!({"type": "string"})
+        }
+        ColumnType::TinyInteger | ColumnType::SmallInteger | ColumnType::Integer => {
+            json!({"type": "integer", "format": "int32"})
+        }
+        ColumnType::BigInteger => json!({"type": "integer", "format": "int64"}),
+        ColumnType::TinyUnsigned
+        | ColumnType::SmallUnsigned
+        | ColumnType::Unsigned
+        | ColumnType::BigUnsigned => json!({"type": "integer", "format": "int64", "minimum": 0}),
+        ColumnType::Float => json!({"type": "number", "format": "float"}),
+        ColumnType::Double => json!({"type": "number", "format": "double"}),
+        ColumnType::Decimal(_) => json!({"type": "string", "format": "decimal"}),
+        ColumnType::DateTime | ColumnType::Timestamp | ColumnType::TimestampWithTimeZone => {
+            json!({"type": "string", "format": "date-time"})
+        }
+        ColumnType::Time => json!({"type": "string", "format": "time"}),
+        ColumnType::Date => json!({"type": "string", "format": "date"}),
+        ColumnType::Binary(_) | ColumnType::VarBinary(_) | ColumnType::Blob => {
+            json!({"type": "string", "format": "byte"})
+        }
+        ColumnType::Boolean => json!({"type": "boolean"}),
+        // json columns are exchanged as their serialized text
+        ColumnType::Json | ColumnType::JsonBinary => {
+            json!({"type": "string", "format": "json"})
+        }
+        ColumnType::Uuid => json!({"type": "string", "format": "uuid"}),
+        ColumnType::Enum { variants, .. } => json!({
+            "type": "string",
+            "enum": variants.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+        }),
+        _ => json!({}),
+    };
+    if def.is_null() {
+        schema["nullable"] = Json::Bool(true);
+    }
+    schema
+}
+
+/// Schema of the `data` of a row, primary keys are optional since the database may generate them
+/// and read-only columns are ignored by updates.
+fn model_schema(model: &(dyn ModelAdminTrait + Send + Sync)) -> Json {
+    let primary_keys = model.get_primary_keys();
+    let readonly_fields = model.get_readonly_fields();
+    let columns = model.get_columns();
+    let properties: serde_json::Map<String, Json> = columns
+        .iter()
+        .map(|(name, def)| {
+            let mut schema = column_schema(def);
+            if primary_keys.contains(name) {
+                schema["x-primary-key"] = Json::Bool(true);
+            }
+            if readonly_fields.contains(name) {
+                schema["readOnly"] = Json::Bool(true);
+            }
+            (name.clone(), schema)
+        })
+        .collect();
+    let required: Vec<&String> = columns
+        .iter()
+        .filter(|(name, def)| {
+            !def.is_null() && !primary_keys.contains(name) && !readonly_fields.contains(name)
+        })
+        .map(|(name, _)| name)
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// Body of `PATCH`, the columns of `model_schema` with none of them required.
+fn patch_schema(model: &(dyn ModelAdminTrait + Send + Sync)) -> Json {
+    let mut schema = model_schema(model);
+    if let Some(schema) = schema.as_object_mut() {
+        schema.remove("required");
+    }
+    schema
+}
+
+fn response(description: &str, schema: &str) -> Json {
+    json!({
+        "description": description,
+        "content": {
+            "application/json": {"schema": {"$ref": format!("#/components/schemas/{}", schema)}},
+        },
+    })
+}
+
+fn error_responses(codes: &[(&str, &str)]) -> serde_json::Map<String, Json> {
+    codes
+        .iter()
+        .map(|(code, description)| (code.to_string(), response(description, "Error")))
+        .collect()
+}
+
+fn request_body(schema: &str) -> Json {
+    json!({
+        "required": true,
+        "content": {
+            "application/json": {"schema": {"$ref": format!("#/components/schemas/{}", schema)}},
+        },
+    })
+}
+
+fn operation(id: String, tag: &str, responses: serde_json::Map<String, Json>) -> Json {
+    json!({
+        "operationId": id,
+        "tags": [tag],
+        "responses": responses,
+    })
+}
+
+/// Paths of the `/api/` routes of one model, operations the user may not run are left out.
+fn model_paths(
+    sub_path: &str,
+    name: &str,
+    model: &(dyn ModelAdminTrait + Send + Sync),
+    user: &AdminUser,
+) -> Vec<(String, Json)> {
+    let object = format!("{}Object", name);
+    let mut collection = serde_json::Map::new();
+    let mut item = serde_json::Map::new();
+
+    let mut responses = error_responses(&[("403", "permission denied")]);
+    responses.insert(
+        "200".into(),
+        response("one page of rows", &format!("{}List", name)),
+    );
+    let mut list = operation(format!("list_{}", name), name, responses);
+    list["parameters"] = json!([
+        {"name": "_q", "in": "query", "schema": {"type": "string"}, "description": "search words"},
+        {"name": "_o", "in": "query", "schema": {"type": "string"},
+            "description": "comma separated columns, prefixed with `-` for descending order"},
+        {"name": "_p", "in": "query", "schema": {"type": "integer", "minimum": 0},
+            "description": "page number, starting from 0"},
+        {"name": "_c", "in": "query", "schema": {"type": "string"},
+            "description": "`next_cursor` or `prev_cursor` of a cursor paginated list, replaces `_p`"},
+    ]);
+    collection.insert("get".into(), list);
+
+    let mut responses = error_responses(&[("404", "no such row")]);
+    responses.insert("200".into(), response("the row", &object));
+    item.insert(
+        "get".into(),
+        operation(format!("get_{}", name), name, responses),
+    );
+
+    if model.has_add_permission(user) {
+        let mut responses = error_responses(&[("400", "invalid request body")]);
+        responses.insert("201".into(), response("the created row", &object));
+        let mut create = operation(format!("create_{}", name), name, responses);
+        create["requestBody"] = request_body(name);
+        collection.insert("post".into(), create);
+    }
+
+    if model.has_change_permission(user) {
+        for (method, description, schema) in [
+            ("put", "replaces every column of the row", name.to_string()),
+            (
+                "patch",
+                "updates the given columns of the row",
+                format!("{}Patch", name),
+            ),
+        ] {
+            let mut responses =
+                error_responses(&[("400", "invalid request body"), ("404", "no such row")]);
+            responses.insert("200".into(), response("the updated row", &object));
+            let mut update = operation(format!("{}_{}", method, name), name, responses);
+            update["description"] = Json::String(description.into());
+            update["requestBody"] = request_body(&schema);
+            item.insert(method.into(), update);
+        }
+    }
+
+    if model.has_delete_permission(user) {
+        let mut responses = error_responses(&[("404", "no such row")]);
+        responses.insert("204".into(), json!({"description": "the row was deleted"}));
+        item.insert(
+            "delete".into(),
+            operation(format!("delete_{}", name), name, responses),
+        );
+    }
+
+    item.insert(
+        "parameters".into(),
+        json!([{
+            "name": "key",
+            "in": "path",
+            "required": true,
+            "schema": {"type": "string"},
+            "description": "the `key` of the row as returned by the api",
+        }]),
+    );
+
+    vec![
+        (
+            format!("{}/api/{}", sub_path, name),
+            Json::Object(collection),
+        ),
+        (
+            format!("{}/api/{}/{{key}}", sub_path, name),
+            Json::Object(item),
+        ),
+    ]
+}
+
+/// OpenAPI 3 document of the `/api/` routes of every model `user` can view.
+pub fn openapi_document(admin: &Admin, user: &AdminUser) -> Json {
+    let mut paths = serde_json::Map::new();
+    let mut schemas = serde_json::Map::new();
+    schemas.insert(
+        "Error".into(),
+        json!({
+            "type": "object",
+            "properties": {
+                "status": {"type": "string"},
+                "error": {"type": "string"},
+            },
+        }),
+    );
+
+    for name in admin.get_site(user).models {
+        let Some(model) = admin.get_model(&name) else {
+            continue;
+        };
+        paths.extend(model_paths(admin.sub_path(), &name, model.as_ref(), user));
+        schemas.insert(name.clone(), model_schema(model.as_ref()));
+        schemas.insert(format!("{}Patch", name), patch_schema(model.as_ref()));
+        schemas.insert(
+            format!("{}Object", name),
+            json!({
+                "type": "object",
+                "properties": {
+                    "key": {"type": "string"},
+                    "label": {"type": "string"},
+                    "data": {"$ref": format!("#/components/schemas/{}", name)},
+                },
+                "required": ["key", "label", "data"],
+            }),
+        );
+        schemas.insert(
+            format!("{}List", name),
+            json!({
+                "type": "object",
+                "properties": {
+                    "total": {"type": "integer", "format": "int64", "nullable": true},
+                    "total_exact": {"type": "boolean"},
+                    "has_next": {"type": "boolean"},
+                    "next_cursor": {"type": "string", "nullable": true},
+                    "prev_cursor": {"type": "string", "nullable": true},
+                    "data": {
+                        "type": "array",
+                        "items": {"$ref": format!("#/components/schemas/{}Object", name)},
+                    },
+                },
+                "required": ["total", "total_exact", "has_next", "data"],
+            }),
+        );
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "seaorm-admin",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {"schemas": schemas},
+    })
+}
@@ -49,12 +49,19 @@ pub fn parse_query(m: &HashMap<String, Vec<String>>, list_per_page: u64) -> Resu
         .and_then(|x| x.parse::<u64>().ok())
         .unwrap_or(0);
 
+    let cursor = m
+        .get("_c")
+        .and_then(|x| x.first())
+        .filter(|x| !x.is_empty())
+        .cloned();
+
     Ok(ListQuery {
         filter,
         queries,
         ordering,
         offset: page * list_per_page,
         limit: list_per_page,
+        cursor,
     })
 }
 
@@ -1,12 +1,13 @@
-use crate::{ListQuery, Result};
+use crate::{ListQuery, ListTotal, Result};
 pub use askama::{DynTemplate, Template};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub struct AdminSite {
     pub title: String,
     pub sub_path: String,
     pub models: Vec<String>,
+    pub addable_models: HashSet<String>,
 }
 
 #[derive(Template, Clone)]
@@ -120,40 +121,147 @@ pub struct RelationForm {
     pub rows: Vec<RelationFormRow>,
 }
 
+#[derive(Template)]
+#[template(path = "display-value.jinja")]
+pub struct AdminDisplayValue {
+    pub label: String,
+    pub value: String,
+    pub link: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "display-table.jinja")]
+pub struct AdminDisplayTable {
+    pub name: String,
+    pub keys: Vec<String>,
+    /// `(link, cells)` of each row
+    pub rows: Vec<(String, Vec<String>)>,
+    /// Number of rows the user is not allowed to see, only counted.
+    pub hidden_rows: usize,
+}
+
+/// Fields of a form rendered together, an untitled group has no frame.
+pub struct FormFieldset {
+    pub title: Option<String>,
+    pub collapsed: bool,
+    pub fields: Vec<Box<dyn DynTemplate + Send>>,
+}
+
 #[derive(Template)]
 #[template(path = "create-form.jinja")]
 pub struct AdminCreateForm {
     pub site: AdminSite,
+    pub csrf_token: String,
     pub form_id: String,
     pub page_id: String,
     pub model_name: String,
     pub action: Option<String>,
     pub method: String,
-    pub fields: Vec<Box<dyn DynTemplate + Send>>,
+    pub fields: Vec<FormFieldset>,
 }
 
 #[derive(Template)]
 #[template(path = "update-form.jinja")]
 pub struct AdminUpdateForm {
     pub site: AdminSite,
+    pub csrf_token: String,
     pub form_id: String,
     pub page_id: String,
     pub model_name: String,
     pub action: Option<String>,
     pub method: String,
-    pub fields: Vec<Box<dyn DynTemplate + Send>>,
+    pub fields: Vec<FormFieldset>,
+    pub can_change: bool,
+    pub can_delete: bool,
+}
+
+#[derive(Template)]
+#[template(path = "detail.jinja")]
+pub struct AdminDetail {
+    pub site: AdminSite,
+    pub page_id: String,
+    pub model_name: String,
+    pub title: String,
+    pub fields: Vec<FormFieldset>,
+    pub can_change: bool,
+    pub can_delete: bool,
+}
+
+/// A line of the tree of related rows on the delete page, either a group of rows or a row.
+pub struct DeleteTreeLine {
+    pub depth: usize,
+    pub label: String,
+    /// `None` for rows of a model the user can not view.
+    pub link: Option<String>,
+    /// What the delete does to the rows of a group, `None` for a row.
+    pub effect: Option<String>,
+    pub blocks: bool,
 }
 
 #[derive(Template)]
 #[template(path = "delete-form.jinja")]
 pub struct AdminDeleteForm {
     pub site: AdminSite,
+    pub csrf_token: String,
     pub form_id: String,
     pub page_id: String,
     pub model_name: String,
     pub action: Option<String>,
     pub method: String,
-    pub fields: Vec<Box<dyn DynTemplate + Send>>,
+    pub fields: Vec<FormFieldset>,
+    pub related: Vec<DeleteTreeLine>,
+    pub blocked: bool,
+    /// Cascades too deep to be checked for blocking rows, the delete is refused as if blocked.
+    pub unchecked: bool,
+}
+
+#[derive(Template)]
+#[template(path = "bulk-delete-form.jinja")]
+pub struct AdminBulkDeleteForm {
+    pub site: AdminSite,
+    pub csrf_token: String,
+    pub form_id: String,
+    pub page_id: String,
+    pub model_name: String,
+    pub action: Option<String>,
+    pub method: String,
+    pub fields: Vec<FormFieldset>,
+    pub keys: String,
+    /// List filters of the rows to delete, `?..`, when all matching rows are selected.
+    pub query: String,
+    pub objects: Vec<(String, String)>,
+    /// Rows to delete, including the ones not listed in `objects`.
+    pub total: u64,
+    pub hidden: u64,
+}
+
+#[derive(Template)]
+#[template(path = "import-form.jinja")]
+pub struct AdminImportForm {
+    pub site: AdminSite,
+    pub csrf_token: String,
+    pub form_id: String,
+    pub page_id: String,
+    pub model_name: String,
+    pub action: Option<String>,
+    pub method: String,
+    pub fields: Vec<FormFieldset>,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdminListFilter {
+    pub name: String,
+    pub searchable: bool,
+    pub options: Vec<AdminListFilterOption>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdminListFilterOption {
+    pub label: String,
+    pub count: Option<u64>,
+    pub link: String,
+    pub is_active: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -168,11 +276,21 @@ pub struct AdminListPage {
 pub struct AdminList {
     pub site: AdminSite,
     pub model_name: String,
+    pub can_add: bool,
+    pub can_delete: bool,
+    pub csrf_token: String,
+    pub actions: Vec<(String, String)>,
+    pub message: Option<String>,
+    pub filters: Vec<AdminListFilter>,
+    pub export_link: String,
     pub keys: Vec<String>,
-    pub rows: Vec<(String, Vec<String>)>,
+    pub rows: Vec<(String, String, Vec<String>)>,
     pub query: ListQuery,
     pub pages: Vec<AdminListPage>,
-    pub total: u64,
+    /// Links to the previous and next page of cursor paginated lists,
+    /// and of offset paginated lists without a total.
+    pub adjacent_links: (Option<String>, Option<String>),
+    pub total: ListTotal,
 }
 
 #[derive(Template)]
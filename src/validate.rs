@@ -0,0 +1,50 @@
+use crate::{AdminError, Result};
+use std::collections::BTreeMap;
+
+/// The write a `validate` function or a save hook is called for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveOperation {
+    Create,
+    Update,
+}
+
+/// Name of `SaveOperation` before the save hooks shared it with `validate`.
+pub type ValidateOperation = SaveOperation;
+
+/// Messages per field returned by a `validate` function, nothing is written unless it is empty.
+///
+/// ```ignore
+/// async fn validate_cake(
+///     model: &cake::ActiveModel,
+///     _conn: &DatabaseTransaction,
+///     _operation: SaveOperation,
+/// ) -> Result<FieldErrors> {
+///     let mut errors = FieldErrors::default();
+///     if let ActiveValue::Set(0) = model.price {
+///         errors.add("price", "must not be free");
+///     }
+///     Ok(errors)
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FieldErrors(pub BTreeMap<String, Vec<String>>);
+
+impl FieldErrors {
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) -> &mut Self {
+        self.0.entry(field.into()).or_default().push(message.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// `AdminError::Validation` unless there are no errors.
+    pub fn into_result(self) -> Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(AdminError::Validation(self.0).into())
+        }
+    }
+}
@@ -34,6 +34,35 @@ function show_error(error) {
   e.classList.remove("d-none");
 }
 
+// shows the messages of `fields` ({name: [message]}) below their inputs and clears the others
+function show_field_errors(form_id, fields) {
+  var form = document.getElementById(form_id);
+
+  var errors = form.getElementsByClassName("form-errors");
+  for (var i = 0; i < errors.length; i++) {
+    errors[i].innerText = "";
+    errors[i].classList.add("d-none");
+  }
+  var invalids = form.querySelectorAll(".is-invalid");
+  for (var i = 0; i < invalids.length; i++) {
+    invalids[i].classList.remove("is-invalid");
+  }
+
+  for (const [name, messages] of Object.entries(fields)) {
+    var input = form.querySelector('[name="' + CSS.escape(name) + '"]');
+    if (!input) {
+      continue;
+    }
+    input.classList.add("is-invalid");
+    var container = input.closest(".mb-3");
+    var e = container ? container.querySelector(".form-errors") : null;
+    if (e) {
+      e.innerText = messages.join(" ");
+      e.classList.remove("d-none");
+    }
+  }
+}
+
 function register_submit_callback(button_id, redirect_url_on_success) {
   document
     .getElementById(button_id)
@@ -44,6 +73,7 @@ function register_submit_callback(button_id, redirect_url_on_success) {
       submit.disabled = true;
 
       let data = extract_form("{{ form_id }}");
+      let csrf_token = document.getElementById("{{ form_id }}").dataset.csrfToken;
       let action = "{% if let Some(action) = action %}{{ action}}{% endif %}";
       let path = action == "" ? window.location.pathname : action;
       fetch(path, {
@@ -51,6 +81,7 @@ function register_submit_callback(button_id, redirect_url_on_success) {
         body: JSON.stringify(data),
         headers: {
           "Content-Type": "application/json",
+          "X-CSRF-Token": csrf_token,
         },
       })
         .then(async function (response) {
@@ -69,6 +100,7 @@ function register_submit_callback(button_id, redirect_url_on_success) {
           }
         })
         .catch(function (error) {
+          show_field_errors("{{ form_id }}", error.fields || {});
           var myDiv = document.getElementById("form-alert");
           // myDiv.innerText = JSON.stringify(error.error);
           myDiv.innerText = error.error;
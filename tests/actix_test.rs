@@ -0,0 +1,72 @@
+#[allow(dead_code)]
+mod model;
+use model::*;
+
+use actix_web::{http::header, test, web, App};
+use admin_macro::ModelAdmin;
+use sea_orm::{DatabaseBackend, MockDatabase};
+use seaorm_admin::{actix_admin, AdminBuilder, AdminUser, BearerTokenAuth, CSRF_COOKIE_NAME};
+use std::{collections::HashMap, sync::Arc};
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake)]
+struct CakeAdmin;
+
+#[tokio::test]
+async fn test_actix_routes() {
+    let cake = cake::Model {
+        id: 1,
+        name: "cheese".into(),
+        price: 300,
+    };
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[std::collections::BTreeMap::from([(
+            "num_items".to_string(),
+            sea_orm::Value::from(1i64),
+        )])]])
+        .append_query_results([[cake]])
+        .into_connection();
+    let users = HashMap::from([("secret".to_string(), AdminUser::superuser("root"))]);
+    let admin = AdminBuilder::default()
+        .add_model(CakeAdmin)
+        .auth(BearerTokenAuth::new(users))
+        .build(Arc::new(connection), "/admin")
+        .unwrap();
+    let admin = web::Data::new(admin);
+    let app = test::init_service(
+        App::new()
+            .app_data(admin.clone())
+            .service(actix_admin::get_scope(admin.sub_path())),
+    )
+    .await;
+
+    let request = test::TestRequest::get().uri("/admin/cake/").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), 401);
+
+    let request = test::TestRequest::get()
+        .uri("/admin/cake/?id=1")
+        .insert_header((header::AUTHORIZATION, "Bearer secret"))
+        .insert_header((header::ACCEPT, "application/json"))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), 200);
+    let cookie = response
+        .headers()
+        .get(header::SET_COOKIE)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(cookie.starts_with(&format!("{}=", CSRF_COOKIE_NAME)));
+    let body: serde_json::Value = test::read_body_json(response).await;
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["data"][0]["data"]["name"], "cheese");
+
+    let request = test::TestRequest::get()
+        .uri("/admin/unknown/")
+        .insert_header((header::AUTHORIZATION, "Bearer secret"))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), 404);
+}
@@ -0,0 +1,112 @@
+#[allow(dead_code)]
+mod model;
+use model::*;
+
+use admin_macro::ModelAdmin;
+use sea_orm::{DatabaseBackend, DatabaseTransaction, MockDatabase, MockExecResult};
+use seaorm_admin::{
+    async_trait, AdminBuilder, AdminUser, AuditAction, AuditEntry, AuditLog, Result,
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake)]
+struct CakeAdmin;
+
+#[derive(Clone, Default)]
+struct MemoryAuditLog(Arc<Mutex<Vec<AuditEntry>>>);
+
+#[async_trait]
+impl AuditLog for MemoryAuditLog {
+    async fn record(&self, _txn: &DatabaseTransaction, entry: &AuditEntry) -> Result<()> {
+        self.0.lock().unwrap().push(entry.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_audit_create_and_delete() {
+    let cake = cake::Model {
+        id: 3,
+        name: "cheese".into(),
+        price: 400,
+    };
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![cake.clone()], vec![cake.clone()], vec![cake.clone()]])
+        .append_exec_results([MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }])
+        .into_connection();
+    let log = MemoryAuditLog::default();
+    let admin = AdminBuilder::default()
+        .add_model(CakeAdmin)
+        .audit_log(log.clone())
+        .build(Arc::new(connection), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+    let user = AdminUser::superuser("alice");
+
+    let data = serde_json::json!({"name": "cheese", "price": 400});
+    admin.create(model, &data, &user, None).await.unwrap();
+    admin
+        .delete(model, &serde_json::json!({"id": 3}), &user, None)
+        .await
+        .unwrap();
+
+    let entries = log.0.lock().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].action, AuditAction::Create);
+    assert_eq!(entries[0].table_name, "cake");
+    assert_eq!(entries[0].object_key, "1.3");
+    assert_eq!(entries[0].actor, "alice");
+    assert_eq!(entries[0].before, None);
+    assert_eq!(entries[0].after.as_ref().unwrap()["name"], "cheese");
+    assert_eq!(entries[1].action, AuditAction::Delete);
+    assert_eq!(entries[1].before.as_ref().unwrap()["price"], 400);
+    assert_eq!(entries[1].after, None);
+}
+
+#[tokio::test]
+async fn test_audit_bulk_delete() {
+    let cakes: Vec<_> = (1..=2)
+        .map(|id| cake::Model {
+            id,
+            name: format!("cake {}", id),
+            price: 100,
+        })
+        .collect();
+    let deleted = MockExecResult {
+        last_insert_id: 0,
+        rows_affected: 1,
+    };
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([
+            vec![cakes[0].clone()],
+            vec![cakes[0].clone()],
+            vec![cakes[1].clone()],
+            vec![cakes[1].clone()],
+        ])
+        .append_exec_results([deleted.clone(), deleted])
+        .into_connection();
+    let log = MemoryAuditLog::default();
+    let admin = AdminBuilder::default()
+        .add_model(CakeAdmin)
+        .audit_log(log.clone())
+        .build(Arc::new(connection), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+    let user = AdminUser::superuser("alice");
+
+    let keys: Vec<String> = cakes
+        .iter()
+        .map(|x| model.json_to_key(&serde_json::json!({"id": x.id})).unwrap())
+        .collect();
+    let count = admin.bulk_delete(model, &keys, &user, None).await.unwrap();
+    assert_eq!(count, 2);
+
+    let entries = log.0.lock().unwrap();
+    let deleted_keys: Vec<_> = entries.iter().map(|x| x.object_key.clone()).collect();
+    assert_eq!(deleted_keys, keys);
+    assert!(entries.iter().all(|x| x.action == AuditAction::Delete));
+}
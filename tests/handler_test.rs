@@ -0,0 +1,265 @@
+#[allow(dead_code)]
+mod model;
+use model::*;
+
+use admin_macro::ModelAdmin;
+use sea_orm::{DatabaseBackend, MockDatabase};
+use seaorm_admin::{
+    AdminBody, AdminBuilder, AdminRequest, AdminUser, BearerTokenAuth, MemorySessionStore,
+    SessionCookieAuth, CSRF_COOKIE_NAME, CSRF_HEADER_NAME,
+};
+use std::{collections::HashMap, sync::Arc};
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake)]
+struct CakeAdmin;
+
+fn cake(id: i32, name: &str, price: u32) -> cake::Model {
+    cake::Model {
+        id,
+        name: name.into(),
+        price,
+    }
+}
+
+#[tokio::test]
+async fn test_handle() {
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[cake(1, "cheese", 300)]])
+        .into_connection();
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeAdmin)
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+
+    let response = admin
+        .clone()
+        .handle(AdminRequest::new("GET", "/admin/"))
+        .await;
+    assert_eq!(response.status, 200);
+    assert!(matches!(response.body, AdminBody::Html(_)));
+    let (_, cookie) = response
+        .headers
+        .iter()
+        .find(|(name, _)| name == "set-cookie")
+        .unwrap();
+    let token = cookie
+        .strip_prefix(&format!("{}=", CSRF_COOKIE_NAME))
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+
+    for (method, path, status) in [
+        ("GET", "/other/", 404),
+        ("GET", "/admin/unknown/", 404),
+        ("GET", "/admin/cake/unknown/", 404),
+        ("POST", "/admin/cake/create/", 403),
+    ] {
+        let response = admin.clone().handle(AdminRequest::new(method, path)).await;
+        assert_eq!(response.status, status, "{} {}", method, path);
+    }
+
+    let cookie = format!("{}={}", CSRF_COOKIE_NAME, token);
+    let response = admin
+        .clone()
+        .handle(
+            AdminRequest::new("POST", "/admin/cake/create/")
+                .header("Cookie", &cookie)
+                .header(CSRF_HEADER_NAME, &token)
+                .body("not json"),
+        )
+        .await;
+    assert_eq!(response.status, 400);
+    assert!(response
+        .headers
+        .iter()
+        .all(|(name, _)| name != "set-cookie"));
+
+    let response = admin
+        .clone()
+        .handle(
+            AdminRequest::new("PUT", "/admin/cake/")
+                .header("Cookie", &cookie)
+                .header(CSRF_HEADER_NAME, &token),
+        )
+        .await;
+    assert_eq!(response.status, 405);
+
+    let response = admin
+        .clone()
+        .handle(
+            AdminRequest::new("POST", "/admin/cake/update/1/")
+                .header("Cookie", &cookie)
+                .header(CSRF_HEADER_NAME, &token)
+                .body(r#"{"name": "cheese", "price": "350"}"#),
+        )
+        .await;
+    assert_eq!(response.status, 200);
+    let json = response.get_json().unwrap();
+    assert_eq!(json["status"], "ok");
+    assert_eq!(json["data"]["name"], "cheese");
+}
+
+#[tokio::test]
+async fn test_api() {
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[std::collections::BTreeMap::from([(
+            "num_items".to_string(),
+            sea_orm::Value::from(1i64),
+        )])]])
+        .append_query_results([[cake(1, "cheese", 300)]])
+        .append_query_results([[cake(1, "cheese", 300)]])
+        .append_query_results([Vec::<cake::Model>::new()])
+        .append_query_results([[cake(1, "cheese", 300)]])
+        .append_query_results([[cake(1, "cheese", 350)]])
+        .append_query_results([[cake(2, "chocolate", 400)]])
+        .append_query_results([[cake(2, "chocolate", 400)]])
+        .append_query_results([[cake(2, "chocolate", 400)]])
+        .append_exec_results([sea_orm::MockExecResult {
+            last_insert_id: 0,
+            rows_affected: 1,
+        }])
+        .into_connection();
+    let users = HashMap::from([("token".to_string(), AdminUser::superuser("root"))]);
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeAdmin)
+            .auth(BearerTokenAuth::new(users))
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+    let request = |method: &str, path: &str| {
+        AdminRequest::new(method, path).header("Authorization", "Bearer token")
+    };
+
+    let response = admin
+        .clone()
+        .handle(request("GET", "/admin/api/openapi.json"))
+        .await;
+    assert_eq!(response.status, 200);
+    let document = response.get_json().unwrap();
+    assert_eq!(document["openapi"], "3.0.3");
+    let cake_schema = &document["components"]["schemas"]["cake"];
+    assert_eq!(cake_schema["properties"]["id"]["x-primary-key"], true);
+    assert_eq!(cake_schema["properties"]["name"]["type"], "string");
+    assert_eq!(
+        cake_schema["required"],
+        serde_json::json!(["name", "price"])
+    );
+    let item = &document["paths"]["/admin/api/cake/{key}"];
+    for method in ["get", "put", "patch", "delete"] {
+        assert!(item[method].is_object(), "{}", method);
+    }
+    // PATCH sends any subset of the columns
+    assert_eq!(
+        item["patch"]["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/cakePatch"
+    );
+    let patch_schema = &document["components"]["schemas"]["cakePatch"];
+    assert!(patch_schema["required"].is_null());
+    assert_eq!(patch_schema["properties"], cake_schema["properties"]);
+    assert!(document["paths"]["/admin/api/cake"]["post"].is_object());
+
+    let response = admin
+        .clone()
+        .handle(request("GET", "/admin/api/cake"))
+        .await;
+    assert_eq!(response.status, 200);
+    let json = response.get_json().unwrap();
+    assert_eq!(json["total"], 1);
+    assert_eq!(json["data"][0]["data"]["name"], "cheese");
+
+    let response = admin
+        .clone()
+        .handle(request("GET", "/admin/api/cake/1"))
+        .await;
+    assert_eq!(response.status, 200);
+    assert_eq!(response.get_json().unwrap()["key"], "1.1");
+
+    let response = admin
+        .clone()
+        .handle(request("GET", "/admin/api/cake/9"))
+        .await;
+    assert_eq!(response.status, 404);
+
+    let response = admin
+        .clone()
+        .handle(request("GET", "/admin/api/cake/1-2"))
+        .await;
+    assert_eq!(response.status, 404);
+
+    let response = admin
+        .clone()
+        .handle(request("POST", "/admin/api/cake").body(r#"{"name": "cheese", "price": "abc"}"#))
+        .await;
+    assert_eq!(response.status, 400);
+    let json = response.get_json().unwrap();
+    assert_eq!(json["status"], "failed");
+    assert_eq!(json["fields"]["price"][0], "invalid digit found in string");
+
+    let response = admin
+        .clone()
+        .handle(request("PUT", "/admin/api/cake/1").body(r#"{"name": "cheese"}"#))
+        .await;
+    assert_eq!(response.status, 400);
+    assert_eq!(
+        response.get_json().unwrap()["error"],
+        "missing columns: price"
+    );
+
+    let response = admin
+        .clone()
+        .handle(request("PATCH", "/admin/api/cake/1").body(r#"{"price": 350}"#))
+        .await;
+    assert_eq!(response.status, 200);
+    assert_eq!(response.get_json().unwrap()["data"]["price"], 350);
+
+    let response = admin
+        .clone()
+        .handle(request("POST", "/admin/api/cake").body(r#"{"name": "chocolate", "price": 400}"#))
+        .await;
+    assert_eq!(response.status, 201);
+    assert_eq!(response.get_json().unwrap()["data"]["id"], 2);
+
+    let response = admin
+        .clone()
+        .handle(request("DELETE", "/admin/api/cake/2"))
+        .await;
+    assert_eq!(response.status, 204);
+
+    let response = admin
+        .clone()
+        .handle(request("POST", "/admin/api/cake/2").body("{}"))
+        .await;
+    assert_eq!(response.status, 405);
+}
+
+#[tokio::test]
+async fn test_csrf_with_session_and_authorization() {
+    let connection = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+    let sessions = Arc::new(MemorySessionStore::default());
+    let session_id = sessions.login(AdminUser::superuser("root"));
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeAdmin)
+            .auth(SessionCookieAuth::new("session", "/login", sessions))
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+
+    // the header does not authenticate the request, the session cookie still needs the token
+    let response = admin
+        .clone()
+        .handle(
+            AdminRequest::new("POST", "/admin/cake/create/")
+                .header("Cookie", &format!("session={}", session_id))
+                .header("Authorization", "Bearer anything")
+                .body(r#"{"name": "cheese", "price": 300}"#),
+        )
+        .await;
+    assert_eq!(response.status, 403);
+}
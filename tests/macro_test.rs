@@ -1,15 +1,861 @@
+#[allow(dead_code)]
 mod model;
 use model::*;
 
 use admin_macro::ModelAdmin;
-use seaorm_admin::Admin;
+use sea_orm::{ActiveValue, DatabaseBackend, DatabaseTransaction, MockDatabase};
+use seaorm_admin::{
+    ActionFuture, Admin, AdminBody, AdminBuilder, AdminError, AdminPermission, AdminRequest,
+    AdminUser, FieldErrors, Json, ModelAdminTrait, SaveOperation,
+};
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(ModelAdmin, Default)]
 #[model_admin(module = cake)]
 struct CakeAdmin;
 
+fn bakery_permission(user: &AdminUser, permission: AdminPermission) -> bool {
+    permission == AdminPermission::View || user.is_superuser
+}
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = bakery, permission = bakery_permission)]
+struct BakeryAdmin;
+
+fn mark_sold_out<'a>(_txn: &'a DatabaseTransaction, rows: Vec<Json>) -> ActionFuture<'a> {
+    Box::pin(async move { Ok(format!("{} cakes sold out", rows.len())) })
+}
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, actions = [mark_sold_out])]
+struct CakeActionAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, list_filter = [Name])]
+struct CakeFilterAdmin;
+
+async fn validate_cake(
+    model: &cake::ActiveModel,
+    _conn: &DatabaseTransaction,
+    operation: SaveOperation,
+) -> seaorm_admin::Result<FieldErrors> {
+    let mut errors = FieldErrors::default();
+    if let ActiveValue::Set(0) = model.price {
+        errors.add("price", "must not be free");
+    }
+    if operation == SaveOperation::Create && model.name.as_ref().is_empty() {
+        errors.add("name", "required");
+    }
+    Ok(errors)
+}
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, validate = validate_cake)]
+struct CakeValidateAdmin;
+
+static HOOK_CALLS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+async fn stamp_cake(
+    _conn: &DatabaseTransaction,
+    mut value: Json,
+    user: &AdminUser,
+    operation: SaveOperation,
+) -> seaorm_admin::Result<Json> {
+    HOOK_CALLS
+        .lock()
+        .unwrap()
+        .push(format!("before_save {:?}", operation));
+    value["name"] = Json::String(format!(
+        "{} by {}",
+        value["name"].as_str().unwrap(),
+        user.username
+    ));
+    Ok(value)
+}
+
+async fn record_saved(
+    _conn: &DatabaseTransaction,
+    row: &Json,
+    _user: &AdminUser,
+    operation: SaveOperation,
+) -> seaorm_admin::Result<()> {
+    HOOK_CALLS
+        .lock()
+        .unwrap()
+        .push(format!("after_save {:?} {}", operation, row["name"]));
+    Ok(())
+}
+
+async fn refuse_delete(
+    _conn: &DatabaseTransaction,
+    key: &Json,
+    _user: &AdminUser,
+) -> seaorm_admin::Result<()> {
+    Err(anyhow::anyhow!("cake {} is still on sale", key["id"]))
+}
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(
+    module = cake,
+    before_save = stamp_cake,
+    after_save = record_saved,
+    before_delete = refuse_delete
+)]
+struct CakeHookAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, pagination = cursor)]
+struct CakeCursorAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, count_strategy = none)]
+struct CakeUncountedAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, count_strategy = estimated)]
+struct CakeEstimatedAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, search_fields = [Name])]
+struct CakeSearchAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, search_fields = [Name], search_mode = full_text)]
+struct CakeFullTextAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, fields = [Name], readonly_fields = [Price])]
+struct CakeReadonlyAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(
+    module = cake,
+    fieldsets = [("Basic", [Name]), ("Pricing", [Price], collapsed)]
+)]
+struct CakeFieldsetAdmin;
+
 #[test]
 fn test_default() {
+    let connection = Arc::new(sea_orm::DatabaseConnection::Disconnected);
     let mut admin = Admin::new(connection, "/admin");
     admin.add_model(CakeAdmin);
 }
+
+#[test]
+fn test_permission() {
+    let user = AdminUser::new("staff").with_perm("cake", AdminPermission::Change);
+    assert!(CakeAdmin.has_view_permission(&user));
+    assert!(CakeAdmin.has_change_permission(&user));
+    assert!(!CakeAdmin.has_add_permission(&user));
+    assert!(!CakeAdmin.has_delete_permission(&user));
+
+    assert!(BakeryAdmin.has_view_permission(&user));
+    assert!(!BakeryAdmin.has_change_permission(&user));
+    assert!(BakeryAdmin.has_delete_permission(&AdminUser::superuser("root")));
+}
+
+#[tokio::test]
+async fn test_actions() {
+    let actions = CakeActionAdmin.get_actions();
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].name, "mark_sold_out");
+    assert_eq!(actions[0].label, "mark sold out");
+    assert!(CakeAdmin.get_actions().is_empty());
+
+    let connection = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+    let admin = AdminBuilder::default()
+        .add_model(CakeActionAdmin)
+        .build(Arc::new(connection), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+    let rows = vec![serde_json::json!({"id": 1}), serde_json::json!({"id": 2})];
+    let message = admin
+        .run_action(model, "mark_sold_out", rows, None)
+        .await
+        .unwrap();
+    assert_eq!(message, "2 cakes sold out");
+    assert!(admin
+        .run_action(model, "unknown", vec![], None)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_list_filter() {
+    assert_eq!(CakeFilterAdmin.list_filter(), vec!["name".to_string()]);
+    assert!(CakeAdmin.list_filter().is_empty());
+
+    let counts = |rows: Vec<(&str, i64)>| {
+        rows.into_iter()
+            .map(|(name, count)| {
+                std::collections::BTreeMap::from([
+                    ("value".to_string(), sea_orm::Value::from(name)),
+                    ("count".to_string(), sea_orm::Value::from(count)),
+                ])
+            })
+            .collect::<Vec<_>>()
+    };
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([counts(vec![("cheese", 2), ("apple", 3)])])
+        .into_connection();
+    let admin = AdminBuilder::default()
+        .add_model(CakeFilterAdmin)
+        .build(Arc::new(connection), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+    let query = HashMap::from([
+        ("name".to_string(), vec!["apple".to_string()]),
+        ("price__gte".to_string(), vec!["10".to_string()]),
+        ("_p".to_string(), vec!["2".to_string()]),
+    ]);
+    let filters = seaorm_admin::get_list_filters(
+        &admin,
+        &AdminUser::superuser("root"),
+        model.as_ref(),
+        &query,
+    )
+    .await
+    .unwrap();
+    assert_eq!(filters.len(), 1);
+    let options: Vec<_> = filters[0]
+        .options
+        .iter()
+        .map(|x| (x.label.as_str(), x.count, x.link.as_str(), x.is_active))
+        .collect();
+    assert_eq!(
+        options,
+        vec![
+            ("all", None, "?price__gte=10", false),
+            ("apple", Some(3), "?price__gte=10&name=apple", true),
+            ("cheese", Some(2), "?price__gte=10&name=cheese", false),
+        ]
+    );
+}
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = topping, auto_complete = [Cake], list_filter = [CakeId])]
+struct ToppingFilterAdmin;
+
+#[tokio::test]
+async fn test_foreign_key_list_filter() {
+    let counts = vec![
+        std::collections::BTreeMap::from([
+            ("value".to_string(), sea_orm::Value::from(1)),
+            ("count".to_string(), sea_orm::Value::from(2i64)),
+        ]),
+        std::collections::BTreeMap::from([
+            ("value".to_string(), sea_orm::Value::from(4)),
+            ("count".to_string(), sea_orm::Value::from(1i64)),
+        ]),
+    ];
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([counts])
+        .append_query_results([[
+            cake::Model {
+                id: 1,
+                name: "cheese".into(),
+                price: 300,
+            },
+            cake::Model {
+                id: 4,
+                name: "apple".into(),
+                price: 200,
+            },
+        ]])
+        .into_connection();
+    let connection = Arc::new(connection);
+    let admin = AdminBuilder::default()
+        .add_model(CakeAdmin)
+        .add_model(ToppingFilterAdmin)
+        .build(connection.clone(), "/admin")
+        .unwrap();
+    let model = admin.get_model("topping").unwrap();
+    let filters = seaorm_admin::get_list_filters(
+        &admin,
+        &AdminUser::superuser("root"),
+        model.as_ref(),
+        &HashMap::new(),
+    )
+    .await
+    .unwrap();
+    let options: Vec<_> = filters[0]
+        .options
+        .iter()
+        .map(|x| (x.label.as_str(), x.count, x.link.as_str()))
+        .collect();
+    assert_eq!(
+        options,
+        vec![
+            ("all", None, "?"),
+            ("cake: [id=1]", Some(2), "?cake_id=1"),
+            ("cake: [id=4]", Some(1), "?cake_id=4"),
+        ]
+    );
+
+    // the most frequent values are counted, then the labels are read with one query,
+    // comparing with integers
+    drop(admin);
+    let log = Arc::try_unwrap(connection).unwrap().into_transaction_log();
+    assert_eq!(log.len(), 2);
+    let statement = log[0].statements()[0].clone();
+    assert!(
+        statement
+            .sql
+            .contains(r#"ORDER BY COUNT("cake_id") DESC, "topping"."cake_id" ASC LIMIT $1"#),
+        "{}",
+        statement.sql
+    );
+    let statement = log[1].statements()[0].clone();
+    assert!(
+        statement.sql.contains(r#""id" IN ($1, $2)"#),
+        "{}",
+        statement.sql
+    );
+    assert_eq!(
+        statement.values.unwrap().0,
+        vec![sea_orm::Value::from(1i64), sea_orm::Value::from(4i64)]
+    );
+}
+
+#[tokio::test]
+async fn test_foreign_key_list_filter_without_view_permission() {
+    let counts = vec![std::collections::BTreeMap::from([
+        ("value".to_string(), sea_orm::Value::from(1)),
+        ("count".to_string(), sea_orm::Value::from(2i64)),
+    ])];
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([counts])
+        .into_connection();
+    let connection = Arc::new(connection);
+    let admin = AdminBuilder::default()
+        .add_model(CakeAdmin)
+        .add_model(ToppingFilterAdmin)
+        .build(connection.clone(), "/admin")
+        .unwrap();
+    let model = admin.get_model("topping").unwrap();
+    let user = AdminUser::new("staff").with_perm("topping", AdminPermission::View);
+    let filters = seaorm_admin::get_list_filters(&admin, &user, model.as_ref(), &HashMap::new())
+        .await
+        .unwrap();
+    let labels: Vec<_> = filters[0]
+        .options
+        .iter()
+        .map(|x| x.label.as_str())
+        .collect();
+    assert_eq!(labels, vec!["all", "1"]);
+
+    // the cakes are not read
+    drop(admin);
+    let log = Arc::try_unwrap(connection).unwrap().into_transaction_log();
+    assert_eq!(log.len(), 1);
+}
+
+#[tokio::test]
+async fn test_export_csv() {
+    use futures_util::TryStreamExt;
+
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[
+            cake::Model {
+                id: 1,
+                name: "cheese".into(),
+                price: 300,
+            },
+            cake::Model {
+                id: 2,
+                name: "apple, \"pie\"".into(),
+                price: 250,
+            },
+        ]])
+        .into_connection();
+    let connection = Arc::new(connection);
+    let admin = AdminBuilder::default()
+        .add_model(CakeAdmin)
+        .build(connection.clone(), "/admin")
+        .unwrap();
+    let query = HashMap::from([
+        ("price__gte".to_string(), vec!["200".to_string()]),
+        ("_o".to_string(), vec!["name".to_string()]),
+    ]);
+    let chunks: Vec<String> = seaorm_admin::export_csv(Arc::new(admin), "cake", &query)
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        chunks.concat(),
+        "id,name,price\r\n1,cheese,300\r\n2,\"apple, \"\"pie\"\"\",250\r\n"
+    );
+
+    // the primary key breaks ties of the ordering, batches are seeked instead of offset
+    let log = format!(
+        "{:?}",
+        Arc::try_unwrap(connection).unwrap().into_transaction_log()
+    );
+    assert!(
+        log.contains(r#"ORDER BY \"cake\".\"name\" ASC, \"cake\".\"id\" ASC LIMIT $2"#),
+        "{}",
+        log
+    );
+}
+
+#[tokio::test]
+async fn test_import() {
+    use seaorm_admin::{parse_import, ImportDecision, ImportFormat};
+
+    let cake = |id, name: &str, price| cake::Model {
+        id,
+        name: name.into(),
+        price,
+    };
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[cake(1, "cheese", 300)]])
+        .append_query_results([[cake(1, "cheese", 350)]])
+        .append_query_results([[cake(2, "apple", 250)]])
+        .into_connection();
+    let admin = AdminBuilder::default()
+        .add_model(CakeAdmin)
+        .build(Arc::new(connection), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+    let rows = parse_import(
+        ImportFormat::Csv,
+        "id,name,price,note\n1,cheese,350,x\n,apple,250,\n,bad,abc,\n",
+    )
+    .unwrap();
+    let result = admin
+        .import(model, rows, &AdminUser::superuser("root"), false)
+        .await
+        .unwrap();
+
+    assert_eq!(result.ignored_columns, vec!["note".to_string()]);
+    let rows: Vec<_> = result
+        .rows
+        .iter()
+        .map(|x| (x.line, x.decision, x.key.clone(), x.errors.len()))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            (1, ImportDecision::Update, Some("1.1".to_string()), 0),
+            (2, ImportDecision::Create, Some("1.2".to_string()), 0),
+            (3, ImportDecision::Create, None, 1),
+        ]
+    );
+    // the invalid row rolls back the whole import
+    assert!(!result.committed);
+
+    let user = AdminUser::new("staff").with_perm("cake", AdminPermission::Change);
+    let rows = parse_import(ImportFormat::Json, r#"[{"name": "new", "price": 1}]"#).unwrap();
+    let result = admin.import(model, rows, &user, true).await.unwrap();
+    assert_eq!(result.rows[0].errors, vec!["permission denied".to_string()]);
+
+    // a failed lookup of the key is reported instead of inserting the row again
+    let rows = parse_import(ImportFormat::Csv, "id,name,price\n1,cheese,350\n").unwrap();
+    let result = admin
+        .import(model, rows, &AdminUser::superuser("root"), false)
+        .await
+        .unwrap();
+    assert_eq!(result.rows[0].decision, ImportDecision::Error);
+    assert_eq!(result.rows[0].key, None);
+    assert_eq!(result.rows[0].errors.len(), 1);
+    assert!(!result.committed);
+}
+
+#[tokio::test]
+async fn test_validate() {
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[cake::Model {
+            id: 1,
+            name: "cheese".into(),
+            price: 350,
+        }]])
+        .into_connection();
+    let admin = AdminBuilder::default()
+        .add_model(CakeValidateAdmin)
+        .build(Arc::new(connection), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+    let user = AdminUser::superuser("root");
+
+    let error = admin
+        .create(
+            model,
+            &serde_json::json!({"name": "", "price": 0}),
+            &user,
+            None,
+        )
+        .await
+        .unwrap_err();
+    let (status, _, fields) = AdminError::classify(&error).unwrap();
+    assert_eq!(status, 400);
+    assert_eq!(fields["price"], vec!["must not be free".to_string()]);
+    assert_eq!(fields["name"], vec!["required".to_string()]);
+
+    let data = admin
+        .update(
+            model,
+            &serde_json::json!({"id": 1, "price": 350}),
+            &user,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(data["price"], 350);
+}
+
+#[tokio::test]
+async fn test_hooks() {
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[cake::Model {
+            id: 1,
+            name: "cheese by root".into(),
+            price: 300,
+        }]])
+        .append_query_results([[cake::Model {
+            id: 2,
+            name: "cheese".into(),
+            price: 300,
+        }]])
+        .into_connection();
+    let admin = AdminBuilder::default()
+        .add_model(CakeHookAdmin)
+        .build(Arc::new(connection), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+    let user = AdminUser::superuser("root");
+
+    let data = admin
+        .create(
+            model,
+            &serde_json::json!({"name": "cheese", "price": 300}),
+            &user,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(data["name"], "cheese by root");
+    assert_eq!(
+        *HOOK_CALLS.lock().unwrap(),
+        vec![
+            "before_save Create".to_string(),
+            "after_save Create \"cheese by root\"".to_string(),
+        ]
+    );
+
+    let error = admin
+        .delete(model, &serde_json::json!({"id": 1}), &user, None)
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "cake 1 is still on sale");
+
+    // a dry run of the import does not run the hooks
+    HOOK_CALLS.lock().unwrap().clear();
+    let rows = seaorm_admin::parse_import(
+        seaorm_admin::ImportFormat::Json,
+        r#"[{"name": "cheese", "price": 300}]"#,
+    )
+    .unwrap();
+    let result = admin.import(model, rows, &user, true).await.unwrap();
+    assert!(!result.has_errors());
+    assert!(HOOK_CALLS.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cursor_pagination() {
+    let cakes = |ids: std::ops::RangeInclusive<i32>| -> Vec<cake::Model> {
+        ids.rev()
+            .map(|id| cake::Model {
+                id,
+                name: format!("cake{}", id),
+                price: 100,
+            })
+            .collect()
+    };
+    let connection = Arc::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([cakes(10..=60)])
+            .append_query_results([cakes(1..=10)])
+            .into_connection(),
+    );
+    let admin = AdminBuilder::default()
+        .add_model(CakeCursorAdmin)
+        .build(connection.clone(), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+
+    let page = admin
+        .get_list_as_json(model, &HashMap::new())
+        .await
+        .unwrap();
+    // cursor pagination does not count the rows by default
+    assert!(page["total"].is_null());
+    assert_eq!(page["data"].as_array().unwrap().len(), 50);
+    assert_eq!(page["data"][49]["key"], "1.11");
+    assert!(page["prev_cursor"].is_null());
+    let next_cursor = page["next_cursor"].as_str().unwrap().to_string();
+
+    let query = HashMap::from([("_c".to_string(), vec![next_cursor])]);
+    let page = admin.get_list_as_json(model, &query).await.unwrap();
+    assert_eq!(page["data"].as_array().unwrap().len(), 10);
+    assert!(page["next_cursor"].is_null());
+    assert!(page["prev_cursor"].is_string());
+
+    drop(admin);
+    let log = format!(
+        "{:?}",
+        Arc::try_unwrap(connection).unwrap().into_transaction_log()
+    );
+    assert!(
+        log.contains(r#"WHERE \"id\" < $1 ORDER BY \"cake\".\"id\" DESC LIMIT $2"#),
+        "{}",
+        log
+    );
+    assert!(!log.contains("COUNT"), "{}", log);
+}
+
+#[tokio::test]
+async fn test_count_strategy() {
+    let cakes = |ids: std::ops::RangeInclusive<i32>| -> Vec<cake::Model> {
+        ids.rev()
+            .map(|id| cake::Model {
+                id,
+                name: format!("cake{}", id),
+                price: 100,
+            })
+            .collect()
+    };
+
+    let connection = Arc::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([cakes(1..=51)])
+            .into_connection(),
+    );
+    let admin = AdminBuilder::default()
+        .add_model(CakeUncountedAdmin)
+        .build(connection.clone(), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+    let page = admin
+        .get_list_as_json(model, &HashMap::new())
+        .await
+        .unwrap();
+    assert!(page["total"].is_null());
+    assert_eq!(page["total_exact"], false);
+    assert_eq!(page["has_next"], true);
+    assert_eq!(page["data"].as_array().unwrap().len(), 50);
+    drop(admin);
+    let log = format!(
+        "{:?}",
+        Arc::try_unwrap(connection).unwrap().into_transaction_log()
+    );
+    assert!(!log.contains("COUNT"), "{}", log);
+    assert!(log.contains("LIMIT $1"), "{}", log);
+
+    let connection = Arc::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[std::collections::BTreeMap::from([(
+                "num_items".to_string(),
+                sea_orm::Value::from(1200i64),
+            )])]])
+            .append_query_results([cakes(1..=3)])
+            .append_query_results([[std::collections::BTreeMap::from([(
+                "QUERY PLAN".to_string(),
+                sea_orm::Value::from(serde_json::json!([{"Plan": {"Plan Rows": 40.0}}])),
+            )])]])
+            .append_query_results([cakes(1..=3)])
+            .into_connection(),
+    );
+    let admin = AdminBuilder::default()
+        .add_model(CakeEstimatedAdmin)
+        .build(connection.clone(), "/admin")
+        .unwrap();
+    let model = admin.get_model("cake").unwrap();
+    let page = admin
+        .get_list_as_json(model, &HashMap::new())
+        .await
+        .unwrap();
+    assert_eq!(page["total"], 1200);
+    assert_eq!(page["total_exact"], false);
+    assert_eq!(page["has_next"], false);
+    let query = HashMap::from([("price__gte".to_string(), vec!["200".to_string()])]);
+    let page = admin.get_list_as_json(model, &query).await.unwrap();
+    assert_eq!(page["total"], 40);
+    drop(admin);
+    let log = format!(
+        "{:?}",
+        Arc::try_unwrap(connection).unwrap().into_transaction_log()
+    );
+    assert!(
+        log.contains("FROM pg_class WHERE oid = to_regclass($1)"),
+        "{}",
+        log
+    );
+    assert!(log.contains("EXPLAIN (FORMAT JSON) SELECT"), "{}", log);
+}
+
+#[tokio::test]
+async fn test_search_fields() {
+    let search = |admin: AdminBuilder| async move {
+        let connection = Arc::new(
+            MockDatabase::new(DatabaseBackend::Postgres)
+                .append_query_results([[std::collections::BTreeMap::from([(
+                    "num_items".to_string(),
+                    sea_orm::Value::from(0i64),
+                )])]])
+                .append_query_results([Vec::<cake::Model>::new()])
+                .into_connection(),
+        );
+        let admin = admin.build(connection.clone(), "/admin").unwrap();
+        let query = HashMap::from([("_q".to_string(), vec!["12".to_string()])]);
+        admin
+            .get_list_as_json(admin.get_model("cake").unwrap(), &query)
+            .await
+            .unwrap();
+        drop(admin);
+        format!(
+            "{:?}",
+            Arc::try_unwrap(connection).unwrap().into_transaction_log()
+        )
+    };
+
+    let log = search(AdminBuilder::default().add_model(CakeSearchAdmin)).await;
+    assert!(log.contains(r#"WHERE \"name\" ILIKE $1"#), "{}", log);
+    assert!(!log.contains(r#"\"id\" ="#), "{}", log);
+
+    let log = search(AdminBuilder::default().add_model(CakeFullTextAdmin)).await;
+    assert!(
+        log.contains(r#"WHERE to_tsvector(concat_ws(' ', \"name\")) @@ plainto_tsquery($1)"#),
+        "{}",
+        log
+    );
+    assert!(
+        log.contains(r#"ORDER BY ts_rank(to_tsvector(concat_ws(' ', \"name\")), plainto_tsquery($2)) DESC, \"cake\".\"id\" DESC"#),
+        "{}",
+        log
+    );
+}
+
+#[tokio::test]
+async fn test_readonly_fields() {
+    let connection = Arc::new(
+        MockDatabase::new(DatabaseBackend::Postgres)
+            .append_query_results([[cake::Model {
+                id: 1,
+                name: "cheese".into(),
+                price: 350,
+            }]])
+            .append_query_results([[cake::Model {
+                id: 1,
+                name: "cheese".into(),
+                price: 350,
+            }]])
+            .append_query_results([[cake::Model {
+                id: 1,
+                name: "cheese".into(),
+                price: 350,
+            }]])
+            .append_query_results([[cake::Model {
+                id: 1,
+                name: "cheese".into(),
+                price: 350,
+            }]])
+            .into_connection(),
+    );
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeReadonlyAdmin)
+            .build(connection.clone(), "/admin")
+            .unwrap(),
+    );
+    let model = admin.get_model("cake").unwrap();
+    assert_eq!(model.get_readonly_fields(), vec!["price".to_string()]);
+
+    let response = admin
+        .clone()
+        .handle(AdminRequest::new("GET", "/admin/cake/update/1/"))
+        .await;
+    assert_eq!(response.status, 200);
+    let html = match response.body {
+        AdminBody::Html(html) => html,
+        _ => panic!("html expected"),
+    };
+    let price = &html[html.find(r#"name="price""#).unwrap()..];
+    assert!(price[..price.find('>').unwrap()].contains("disabled"));
+
+    // changing a read-only value is rejected, sending it back unchanged is not
+    let error = admin
+        .update(
+            model,
+            &serde_json::json!({"id": 1, "name": "cheese", "price": 0}),
+            &AdminUser::superuser("root"),
+            None,
+        )
+        .await
+        .unwrap_err();
+    let (status, _, fields) = AdminError::classify(&error).unwrap();
+    assert_eq!(status, 400);
+    assert_eq!(fields["price"], vec!["read-only".to_string()]);
+
+    let data = admin
+        .update(
+            model,
+            &serde_json::json!({"id": 1, "name": "cheese", "price": "350"}),
+            &AdminUser::superuser("root"),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(data["price"], 350);
+    drop(admin);
+    let log = format!(
+        "{:?}",
+        Arc::try_unwrap(connection).unwrap().into_transaction_log()
+    );
+    assert!(
+        log.contains(r#"UPDATE \"cake\" SET \"name\" = $1 WHERE"#),
+        "{}",
+        log
+    );
+}
+
+#[tokio::test]
+async fn test_fieldsets() {
+    let connection = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([[cake::Model {
+            id: 1,
+            name: "cheese".into(),
+            price: 350,
+        }]])
+        .into_connection();
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeFieldsetAdmin)
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+    let page = |path: &'static str| {
+        let admin = admin.clone();
+        async move {
+            let response = admin.handle(AdminRequest::new("GET", path)).await;
+            assert_eq!(response.status, 200);
+            match response.body {
+                AdminBody::Html(html) => html,
+                _ => panic!("html expected"),
+            }
+        }
+    };
+
+    let html = page("/admin/cake/create/").await;
+    let position = |text: &str| html.find(text).unwrap();
+    assert!(position("<summary class=\"h5\">Basic</summary>") < position(r#"name="name""#));
+    assert!(position(r#"name="name""#) < position("<summary class=\"h5\">Pricing</summary>"));
+    assert!(position("<summary class=\"h5\">Pricing</summary>") < position(r#"name="price""#));
+    assert_eq!(html.matches("<details").count(), 2);
+    assert_eq!(html.matches(" open>").count(), 1);
+    assert!(!html.contains(r#"name="id""#));
+
+    // the primary key is in no fieldset and follows them
+    let html = page("/admin/cake/update/1/").await;
+    assert!(html.find(r#"name="price""#).unwrap() < html.find(r#"name="id""#).unwrap());
+}
@@ -0,0 +1,620 @@
+#[allow(dead_code)]
+mod model;
+use model::*;
+
+use admin_macro::ModelAdmin;
+use askama::Template;
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, EntityTrait, Schema};
+use seaorm_admin::{
+    inline_field, Admin, AdminBody, AdminBuilder, AdminPermission, AdminRequest, AdminResponse,
+    AdminUser, AuditLogAdmin, SeaOrmAuditLog, CSRF_COOKIE_NAME, CSRF_HEADER_NAME,
+};
+use std::sync::Arc;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = cake, search_fields = [Name])]
+struct CakeAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = recipe)]
+struct RecipeAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = sale)]
+struct SaleAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = topping, auto_complete = [Cake])]
+struct ToppingAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = review)]
+struct ReviewAdmin;
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(
+    module = cake,
+    form_fields = [inline_field("toppings", topping::Relation::Cake.def(), true)]
+)]
+struct CakeToppingAdmin;
+
+async fn create_table<E: EntityTrait>(conn: &DatabaseConnection, entity: E) {
+    let backend = conn.get_database_backend();
+    let stmt = Schema::new(backend).create_table_from_entity(entity);
+    conn.execute(backend.build(&stmt)).await.unwrap();
+}
+
+async fn send(
+    admin: &Arc<Admin>,
+    method: &str,
+    path: &str,
+    body: serde_json::Value,
+) -> AdminResponse {
+    let (path, query) = match path.split_once('?') {
+        Some((path, query)) => (path, Some(query)),
+        None => (path, None),
+    };
+    let mut request = AdminRequest::new(method, path)
+        .query(query)
+        .header("Cookie", &format!("{}=token", CSRF_COOKIE_NAME))
+        .header(CSRF_HEADER_NAME, "token");
+    if !body.is_null() {
+        request = request
+            .header("Content-Type", "application/json")
+            .body(body.to_string());
+    }
+    admin.clone().handle(request).await
+}
+
+fn steps_of(response: &AdminResponse) -> serde_json::Value {
+    let json = response.get_json().unwrap();
+    serde_json::from_str(json["data"]["steps"].as_str().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_sqlite() {
+    let connection = Database::connect("sqlite::memory:").await.unwrap();
+    create_table(&connection, cake::Entity).await;
+    create_table(&connection, recipe::Entity).await;
+    create_table(&connection, sale::Entity).await;
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeAdmin)
+            .add_model(RecipeAdmin)
+            .add_model(SaleAdmin)
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+
+    for (name, price) in [("Cheese", 300), ("Apple pie", 250), ("chocolate", 400)] {
+        let response = send(
+            &admin,
+            "POST",
+            "/admin/api/cake",
+            serde_json::json!({"name": name, "price": price}),
+        )
+        .await;
+        assert_eq!(response.status, 201, "{:?}", response.get_json());
+    }
+
+    let response = send(
+        &admin,
+        "GET",
+        "/admin/api/cake?_q=CHEESE",
+        serde_json::Value::Null,
+    )
+    .await;
+    assert_eq!(response.status, 200);
+    let json = response.get_json().unwrap();
+    assert_eq!(json["total"], 1);
+    assert_eq!(json["data"][0]["data"]["name"], "Cheese");
+
+    let response = send(
+        &admin,
+        "GET",
+        "/admin/api/cake?price__gte=300&_o=-price",
+        serde_json::Value::Null,
+    )
+    .await;
+    let json = response.get_json().unwrap();
+    assert_eq!(json["total"], 2);
+    assert_eq!(json["data"][0]["data"]["name"], "chocolate");
+
+    let response = send(&admin, "GET", "/admin/cake/", serde_json::Value::Null).await;
+    assert_eq!(response.status, 200);
+
+    let response = send(
+        &admin,
+        "PATCH",
+        "/admin/api/cake/1",
+        serde_json::json!({"price": 320}),
+    )
+    .await;
+    assert_eq!(response.status, 200, "{:?}", response.get_json());
+    assert_eq!(response.get_json().unwrap()["data"]["price"], 320);
+
+    let response = send(
+        &admin,
+        "DELETE",
+        "/admin/api/cake/2",
+        serde_json::Value::Null,
+    )
+    .await;
+    assert_eq!(response.status, 204);
+    let response = send(&admin, "GET", "/admin/api/cake/2", serde_json::Value::Null).await;
+    assert_eq!(response.status, 404);
+
+    let steps = serde_json::json!([{"step": "mix"}, {"step": "bake"}]);
+    let response = send(
+        &admin,
+        "POST",
+        "/admin/api/recipe",
+        serde_json::json!({"name": "sponge", "steps": steps}),
+    )
+    .await;
+    assert_eq!(response.status, 201, "{:?}", response.get_json());
+    // json columns are shown as their text, as in the form
+    assert_eq!(steps_of(&response), steps);
+    let response = send(
+        &admin,
+        "POST",
+        "/admin/api/recipe",
+        serde_json::json!({"name": "scone", "steps": "[\"knead\"]"}),
+    )
+    .await;
+    assert_eq!(response.status, 201, "{:?}", response.get_json());
+    assert_eq!(steps_of(&response), serde_json::json!(["knead"]));
+    let response = send(
+        &admin,
+        "GET",
+        "/admin/recipe/update/1/",
+        serde_json::Value::Null,
+    )
+    .await;
+    assert_eq!(response.status, 200);
+
+    let response = send(
+        &admin,
+        "POST",
+        "/admin/api/sale",
+        serde_json::json!({"day": "2024-02-29", "region": "eu-west", "amount": 10}),
+    )
+    .await;
+    assert_eq!(response.status, 201, "{:?}", response.get_json());
+    let key = response.get_json().unwrap()["key"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let path = format!("/admin/api/sale/{}", key);
+    let response = send(&admin, "PATCH", &path, serde_json::json!({"amount": 12})).await;
+    assert_eq!(response.status, 200, "{:?}", response.get_json());
+    assert_eq!(response.get_json().unwrap()["data"]["amount"], 12);
+    let response = send(
+        &admin,
+        "GET",
+        &format!("/admin/sale/update/{}/", key),
+        serde_json::Value::Null,
+    )
+    .await;
+    assert_eq!(response.status, 200);
+    let response = send(&admin, "DELETE", &path, serde_json::Value::Null).await;
+    assert_eq!(response.status, 204);
+    let response = send(&admin, "GET", &path, serde_json::Value::Null).await;
+    assert_eq!(response.status, 404);
+}
+
+fn html_of(response: AdminResponse) -> String {
+    assert_eq!(response.status, 200);
+    match response.body {
+        AdminBody::Html(html) => html,
+        _ => panic!("html expected"),
+    }
+}
+
+#[tokio::test]
+async fn test_detail_page() {
+    let connection = Database::connect("sqlite::memory:").await.unwrap();
+    create_table(&connection, cake::Entity).await;
+    create_table(&connection, topping::Entity).await;
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeToppingAdmin)
+            .add_model(ToppingAdmin)
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+
+    let response = send(
+        &admin,
+        "POST",
+        "/admin/api/cake",
+        serde_json::json!({"name": "Cheese", "price": 300, "toppings.state": ""}),
+    )
+    .await;
+    assert_eq!(response.status, 201, "{:?}", response.get_json());
+    let cake_key = response.get_json().unwrap()["key"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let response = send(
+        &admin,
+        "POST",
+        "/admin/api/topping",
+        serde_json::json!({"cake_id": 1, "name": "cream"}),
+    )
+    .await;
+    assert_eq!(response.status, 201, "{:?}", response.get_json());
+    let topping_key = response.get_json().unwrap()["key"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let html = html_of(
+        send(
+            &admin,
+            "GET",
+            &format!("/admin/topping/view/{}/", topping_key),
+            serde_json::Value::Null,
+        )
+        .await,
+    );
+    assert!(!html.contains("<input"), "{}", html);
+    assert!(html.contains(">cream</div>"), "{}", html);
+    assert!(
+        html.contains(&format!(
+            r#"<a href="/admin/cake/view/{}/">cake: [id=1]</a>"#,
+            cake_key
+        )),
+        "{}",
+        html
+    );
+
+    let html = html_of(
+        send(
+            &admin,
+            "GET",
+            &format!("/admin/cake/view/{}/", cake_key),
+            serde_json::Value::Null,
+        )
+        .await,
+    );
+    assert!(html.contains("<h4>toppings</h4>"), "{}", html);
+    assert!(
+        html.contains(&format!(
+            r#"<a href="/admin/topping/view/{}/">"#,
+            topping_key
+        )),
+        "{}",
+        html
+    );
+    assert!(html.contains("<td>cream</td>"), "{}", html);
+
+    // rows of models the user can not view are neither labeled nor linked
+    let user = AdminUser::new("staff").with_perm("topping", AdminPermission::View);
+    let topping = serde_json::json!({"id": 1, "cake_id": 1, "name": "cream"});
+    let html = admin
+        .get_detail_template(
+            admin.get_model("topping").unwrap().as_ref(),
+            &topping,
+            &user,
+        )
+        .await
+        .unwrap()
+        .render()
+        .unwrap();
+    assert!(!html.contains("cake: [id=1]"), "{}", html);
+    assert!(!html.contains("/admin/cake/view/"), "{}", html);
+    let user = AdminUser::new("staff").with_perm("cake", AdminPermission::View);
+    let cake = serde_json::json!({"id": 1, "name": "Cheese", "price": 300});
+    let html = admin
+        .get_detail_template(admin.get_model("cake").unwrap().as_ref(), &cake, &user)
+        .await
+        .unwrap()
+        .render()
+        .unwrap();
+    assert!(html.contains("rows: 1"), "{}", html);
+    assert!(!html.contains("cream"), "{}", html);
+
+    let response = send(
+        &admin,
+        "GET",
+        "/admin/cake/view/9/",
+        serde_json::Value::Null,
+    )
+    .await;
+    assert_eq!(response.status, 404);
+}
+
+#[tokio::test]
+async fn test_delete_page() {
+    let connection = Database::connect("sqlite::memory:").await.unwrap();
+    create_table(&connection, cake::Entity).await;
+    create_table(&connection, topping::Entity).await;
+    create_table(&connection, review::Entity).await;
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeToppingAdmin)
+            .add_model(ToppingAdmin)
+            .add_model(ReviewAdmin)
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+    for (path, data) in [
+        (
+            "/admin/api/cake",
+            serde_json::json!({"name": "Cheese", "price": 300, "toppings.state": ""}),
+        ),
+        (
+            "/admin/api/topping",
+            serde_json::json!({"cake_id": 1, "name": "cream"}),
+        ),
+        (
+            "/admin/api/review",
+            serde_json::json!({"topping_id": 1, "text": "good"}),
+        ),
+    ] {
+        let response = send(&admin, "POST", path, data).await;
+        assert_eq!(response.status, 201, "{:?}", response.get_json());
+    }
+
+    let html = html_of(
+        send(
+            &admin,
+            "GET",
+            "/admin/cake/delete/1.1/",
+            serde_json::Value::Null,
+        )
+        .await,
+    );
+    assert!(
+        html.contains(r#"<a href="/admin/topping/?cake_id=1">1 topping (cake_id)</a>"#),
+        "{}",
+        html
+    );
+    assert!(html.contains(r#"<a href="/admin/topping/view/1.1/">topping: [id=1]</a>"#));
+    assert!(html.contains(r#"<a href="/admin/review/?topping_id=1">1 review (topping_id)</a>"#));
+    assert!(html.contains("blocks the delete"));
+    assert!(html.contains(r#"class="btn btn-danger" disabled>confirm delete"#));
+
+    let response = send(
+        &admin,
+        "DELETE",
+        "/admin/api/review/1.1",
+        serde_json::Value::Null,
+    )
+    .await;
+    assert_eq!(response.status, 204);
+    let html = html_of(
+        send(
+            &admin,
+            "GET",
+            "/admin/cake/delete/1.1/",
+            serde_json::Value::Null,
+        )
+        .await,
+    );
+    assert!(html.contains(">deleted</span>"), "{}", html);
+    assert!(!html.contains("blocks the delete"));
+    assert!(
+        html.contains(r#"class="btn btn-danger" >confirm delete"#),
+        "{}",
+        html
+    );
+
+    let response = send(
+        &admin,
+        "DELETE",
+        "/admin/api/cake/1.1",
+        serde_json::Value::Null,
+    )
+    .await;
+    assert_eq!(response.status, 204);
+    let response = send(&admin, "GET", "/admin/api/topping", serde_json::Value::Null).await;
+    assert_eq!(response.get_json().unwrap()["total"], 0);
+}
+
+#[derive(ModelAdmin, Default)]
+#[model_admin(module = category)]
+struct CategoryAdmin;
+
+#[tokio::test]
+async fn test_delete_page_limits() {
+    let connection = Database::connect("sqlite::memory:").await.unwrap();
+    create_table(&connection, cake::Entity).await;
+    create_table(&connection, topping::Entity).await;
+    create_table(&connection, review::Entity).await;
+    create_table(&connection, category::Entity).await;
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeAdmin)
+            .add_model(ToppingAdmin)
+            .add_model(ReviewAdmin)
+            .add_model(CategoryAdmin)
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+    let response = send(
+        &admin,
+        "POST",
+        "/admin/api/cake",
+        serde_json::json!({"name": "Cheese", "price": 300}),
+    )
+    .await;
+    assert_eq!(response.status, 201, "{:?}", response.get_json());
+    for i in 1..=25 {
+        let response = send(
+            &admin,
+            "POST",
+            "/admin/api/topping",
+            serde_json::json!({"cake_id": 1, "name": format!("topping{}", i)}),
+        )
+        .await;
+        assert_eq!(response.status, 201, "{:?}", response.get_json());
+    }
+    let response = send(
+        &admin,
+        "POST",
+        "/admin/api/review",
+        serde_json::json!({"topping_id": 25, "text": "good"}),
+    )
+    .await;
+    assert_eq!(response.status, 201, "{:?}", response.get_json());
+
+    // only 20 toppings are listed, the review of an unlisted one still blocks the delete
+    let html = html_of(
+        send(
+            &admin,
+            "GET",
+            "/admin/cake/delete/1.1/",
+            serde_json::Value::Null,
+        )
+        .await,
+    );
+    assert!(html.contains(">25 topping (cake_id)</a>"), "{}", html);
+    assert_eq!(html.matches("/admin/topping/view/").count(), 20);
+    assert!(html.contains(r#"class="btn btn-danger" disabled>confirm delete"#));
+
+    // rows of models the user can not view are counted without labels or links
+    let user = AdminUser::new("staff")
+        .with_perm("cake", AdminPermission::View)
+        .with_perm("cake", AdminPermission::Delete);
+    let model = admin.get_model("cake").unwrap();
+    let cake = serde_json::json!({"id": 1, "name": "Cheese", "price": 300});
+    let html = admin
+        .get_delete_template(model, &cake, &user, "token")
+        .await
+        .unwrap()
+        .render()
+        .unwrap();
+    assert!(html.contains("25 topping (cake_id)"), "{}", html);
+    assert!(!html.contains("/admin/topping/"), "{}", html);
+    assert!(!html.contains("topping1"), "{}", html);
+
+    // a cascade deeper than the walked levels is not confirmed
+    let mut parent = serde_json::Value::Null;
+    for i in 1..=7 {
+        let response = send(
+            &admin,
+            "POST",
+            "/admin/api/category",
+            serde_json::json!({"parent_id": parent, "name": format!("level{}", i)}),
+        )
+        .await;
+        assert_eq!(response.status, 201, "{:?}", response.get_json());
+        parent = response.get_json().unwrap()["data"]["id"].clone();
+    }
+    let html = html_of(
+        send(
+            &admin,
+            "GET",
+            "/admin/category/delete/1.1/",
+            serde_json::Value::Null,
+        )
+        .await,
+    );
+    assert!(
+        html.contains("more levels of rows than can be checked"),
+        "{}",
+        html
+    );
+    assert!(html.contains(r#"class="btn btn-danger" disabled>confirm delete"#));
+    let html = html_of(
+        send(
+            &admin,
+            "GET",
+            "/admin/category/delete/1.4/",
+            serde_json::Value::Null,
+        )
+        .await,
+    );
+    assert!(!html.contains("more levels of rows"), "{}", html);
+    assert!(html.contains(r#"class="btn btn-danger" >confirm delete"#));
+}
+
+#[tokio::test]
+async fn test_audit_log_table() {
+    let connection = Database::connect("sqlite::memory:").await.unwrap();
+    create_table(&connection, cake::Entity).await;
+    SeaOrmAuditLog::create_table(&connection).await.unwrap();
+    // IF NOT EXISTS, running it again at startup is fine
+    SeaOrmAuditLog::create_table(&connection).await.unwrap();
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeAdmin)
+            .audit_log(SeaOrmAuditLog)
+            .add_model(AuditLogAdmin)
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+
+    let response = send(
+        &admin,
+        "POST",
+        "/admin/api/cake",
+        serde_json::json!({"name": "cheese", "price": 300}),
+    )
+    .await;
+    assert_eq!(response.status, 201, "{:?}", response.get_json());
+
+    let response = send(
+        &admin,
+        "GET",
+        "/admin/api/admin_log",
+        serde_json::Value::Null,
+    )
+    .await;
+    assert_eq!(response.status, 200, "{:?}", response.get_json());
+    let json = response.get_json().unwrap();
+    assert_eq!(json["total"], 1);
+    assert_eq!(json["data"][0]["data"]["table_name"], "cake");
+    assert_eq!(json["data"][0]["data"]["action"], "create");
+}
+
+#[tokio::test]
+async fn test_bulk_delete_matching() {
+    let connection = Database::connect("sqlite::memory:").await.unwrap();
+    create_table(&connection, cake::Entity).await;
+    let admin = Arc::new(
+        AdminBuilder::default()
+            .add_model(CakeAdmin)
+            .build(Arc::new(connection), "/admin")
+            .unwrap(),
+    );
+    for i in 1..=25 {
+        let price = if i <= 22 { 100 } else { 500 };
+        let response = send(
+            &admin,
+            "POST",
+            "/admin/api/cake",
+            serde_json::json!({"name": format!("cake {}", i), "price": price}),
+        )
+        .await;
+        assert_eq!(response.status, 201);
+    }
+
+    // only the first rows are listed, the count covers all of them
+    let html = html_of(
+        send(
+            &admin,
+            "GET",
+            "/admin/cake/bulk-delete/?_all=1&price__lt=200",
+            serde_json::Value::Null,
+        )
+        .await,
+    );
+    assert!(html.contains("The following 22 objects will be deleted."));
+    assert!(html.contains("and 2 more"));
+    assert!(html.contains(r#"name="query" value="?price__lt=200""#));
+
+    let response = send(
+        &admin,
+        "POST",
+        "/admin/cake/bulk-delete/",
+        serde_json::json!({"keys": "", "query": "?price__lt=200"}),
+    )
+    .await;
+    assert_eq!(response.status, 200);
+
+    let response = send(&admin, "GET", "/admin/api/cake", serde_json::Value::Null).await;
+    assert_eq!(response.get_json().unwrap()["total"], 3);
+}